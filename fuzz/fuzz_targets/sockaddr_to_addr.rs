@@ -0,0 +1,29 @@
+//! Feeds arbitrary `sockaddr_storage` bytes and lengths into `sockaddr_to_addr`.
+//!
+//! Run with `cargo fuzz run sockaddr_to_addr`.
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate libc;
+extern crate lazy_socket;
+
+use std::mem;
+use std::ptr;
+use std::cmp;
+
+use lazy_socket::raw::fuzzing::sockaddr_to_addr;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < mem::size_of::<libc::socklen_t>() {
+        return;
+    }
+
+    let (len, bytes) = data.split_at(mem::size_of::<libc::socklen_t>());
+    let len = libc::socklen_t::from_ne_bytes([len[0], len[1], len[2], len[3]]);
+
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let copy_len = cmp::min(bytes.len(), mem::size_of_val(&storage));
+    unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), &mut storage as *mut _ as *mut u8, copy_len) };
+
+    let _ = sockaddr_to_addr(&storage, len);
+});
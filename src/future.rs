@@ -0,0 +1,573 @@
+//! Runtime agnostic futures.
+//!
+//! Provides `AsyncSocket` whose operations are futures that can be driven by any executor.
+//!
+//! Readiness of sockets is awaited by background thread that is spawned on first use.
+//! It waits within `poll()` and wakes pending tasks once their sockets become ready.
+use std::io;
+use std::mem;
+use std::net;
+use std::ptr;
+use std::thread;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicI32, AtomicU64, Ordering};
+use std::task::{Context, Poll, Waker};
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use raw::{
+    libc,
+    Socket,
+    MsgFlags,
+    SocketError,
+    NON_BLOCKING,
+    NON_INHERITABLE
+};
+
+#[derive(Copy, Clone)]
+enum Interest {
+    Read,
+    Write
+}
+
+//Waker of future along with its registration key.
+type Waiter = (u64, Waker);
+
+#[derive(Default)]
+struct Waiters {
+    read: Vec<Waiter>,
+    write: Vec<Waiter>
+}
+
+impl Waiters {
+    fn get_mut(&mut self, interest: Interest) -> &mut Vec<Waiter> {
+        match interest {
+            Interest::Read => &mut self.read,
+            Interest::Write => &mut self.write
+        }
+    }
+}
+
+static REACTOR: OnceLock<Reactor> = OnceLock::new();
+//Serializes start of reactor, so that it is published only once its thread is running.
+static REACTOR_INIT: Mutex<()> = Mutex::new(());
+
+///Background poller.
+struct Reactor {
+    waiters: Mutex<HashMap<RawFd, Waiters>>,
+    //OS error, which stopped reactor's thread, or 0 while it is running.
+    //Changed and checked only under lock of `waiters`.
+    failure: AtomicI32,
+    //Source of registration keys, so that each future can remove only its own waker.
+    next_key: AtomicU64,
+    //Pipe that interrupts `poll()` whenever set of waiters changes.
+    notify_read: RawFd,
+    notify_write: RawFd
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd"))]
+fn notify_pipe() -> io::Result<[RawFd; 2]> {
+    let mut fds = [0 as libc::c_int; 2];
+
+    match unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK) } {
+        0 => Ok(fds),
+        _ => Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd")))]
+fn notify_pipe() -> io::Result<[RawFd; 2]> {
+    let mut fds = [0 as libc::c_int; 2];
+
+    //No `pipe2()` so descriptors are briefly inheritable.
+    unsafe {
+        if libc::pipe(fds.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    for fd in fds.iter() {
+        if let Err(error) = set_nonblocking_cloexec(*fd) {
+            unsafe {
+                libc::close(fds[0]);
+                libc::close(fds[1]);
+            }
+            return Err(error);
+        }
+    }
+
+    Ok(fds)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd")))]
+fn set_nonblocking_cloexec(fd: RawFd) -> io::Result<()> {
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags < 0 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        if libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+impl Reactor {
+    fn new() -> io::Result<Reactor> {
+        let fds = notify_pipe()?;
+
+        Ok(Reactor {
+            waiters: Mutex::new(HashMap::new()),
+            failure: AtomicI32::new(0),
+            next_key: AtomicU64::new(0),
+            notify_read: fds[0],
+            notify_write: fds[1]
+        })
+    }
+
+    ///Returns reactor, spawning its thread on first use.
+    ///
+    ///Fails if pipe or thread cannot be created, in which case next call tries again.
+    fn get() -> io::Result<&'static Reactor> {
+        if let Some(reactor) = REACTOR.get() {
+            return Ok(reactor);
+        }
+
+        let _guard = REACTOR_INIT.lock().unwrap();
+        if let Some(reactor) = REACTOR.get() {
+            return Ok(reactor);
+        }
+
+        let reactor = Reactor::new()?;
+
+        //Thread waits for initialization to complete, which happens only if it is spawned.
+        thread::Builder::new().name("lazy-socket-reactor".to_owned())
+                              .spawn(|| {
+                                  drop(REACTOR_INIT.lock());
+                                  if let Some(reactor) = REACTOR.get() {
+                                      reactor.run();
+                                  }
+                              })?;
+
+        Ok(REACTOR.get_or_init(|| reactor))
+    }
+
+    fn notify(&self) {
+        //Pipe is full only if reactor hasn't woken up yet, so it is fine to ignore failure.
+        unsafe {
+            libc::write(self.notify_write, &1u8 as *const u8 as *const libc::c_void, 1);
+        }
+    }
+
+    ///Schedules wake up of task once socket becomes ready.
+    ///
+    ///Waker of still pending `registration` is updated in place, otherwise new key is stored in it.
+    ///
+    ///Fails if reactor's thread is stopped by error, as nothing would wake task then.
+    fn register(&self, fd: RawFd, interest: Interest, waker: &Waker, registration: &mut Option<u64>) -> io::Result<()> {
+        {
+            let mut waiters = self.waiters.lock().unwrap();
+
+            match self.failure.load(Ordering::Relaxed) {
+                0 => (),
+                code => return Err(io::Error::from_raw_os_error(code))
+            }
+
+            let wakers = waiters.entry(fd).or_default().get_mut(interest);
+
+            if let Some(key) = *registration {
+                if let Some(waiter) = wakers.iter_mut().find(|waiter| waiter.0 == key) {
+                    if !waiter.1.will_wake(waker) {
+                        waiter.1 = waker.clone();
+                    }
+                    return Ok(());
+                }
+            }
+
+            let key = self.next_key.fetch_add(1, Ordering::Relaxed);
+            wakers.push((key, waker.clone()));
+            *registration = Some(key);
+        }
+
+        self.notify();
+        Ok(())
+    }
+
+    ///Removes waker of registration, unless it is already woken.
+    fn unregister(&self, fd: RawFd, interest: Interest, key: u64) {
+        let mut waiters = self.waiters.lock().unwrap();
+
+        let is_empty = match waiters.get_mut(&fd) {
+            Some(entry) => {
+                entry.get_mut(interest).retain(|waiter| waiter.0 != key);
+                entry.read.is_empty() && entry.write.is_empty()
+            }
+            None => return
+        };
+
+        //Reactor stops polling socket on its next iteration.
+        if is_empty {
+            waiters.remove(&fd);
+        }
+    }
+
+    ///Removes socket and wakes all its waiters.
+    fn deregister(&self, fd: RawFd) {
+        let waiters = self.waiters.lock().unwrap().remove(&fd);
+
+        if let Some(waiters) = waiters {
+            for (_, waker) in waiters.read.into_iter().chain(waiters.write) {
+                waker.wake();
+            }
+
+            self.notify();
+        }
+    }
+
+    fn run(&self) {
+        let mut fds = Vec::new();
+        let mut ready = Vec::new();
+        let mut drain = [0u8; 64];
+
+        loop {
+            fds.clear();
+            fds.push(libc::pollfd { fd: self.notify_read, events: libc::POLLIN, revents: 0 });
+
+            for (fd, waiters) in self.waiters.lock().unwrap().iter() {
+                let mut events = 0;
+                if !waiters.read.is_empty() {
+                    events |= libc::POLLIN;
+                }
+                if !waiters.write.is_empty() {
+                    events |= libc::POLLOUT;
+                }
+
+                fds.push(libc::pollfd { fd: *fd, events, revents: 0 });
+            }
+
+            if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) } < 0 {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+
+                //Wake everyone, so that tasks observe failure on next registration instead of hanging.
+                let waiters = {
+                    let mut waiters = self.waiters.lock().unwrap();
+                    self.failure.store(error.raw_os_error().unwrap_or(libc::EIO), Ordering::Relaxed);
+                    mem::take(&mut *waiters)
+                };
+
+                for (_, waker) in waiters.into_values().flat_map(|waiters| waiters.read.into_iter().chain(waiters.write)) {
+                    waker.wake();
+                }
+
+                return;
+            }
+
+            if fds[0].revents != 0 {
+                while unsafe { libc::read(self.notify_read, drain.as_mut_ptr() as *mut libc::c_void, drain.len()) } > 0 {}
+            }
+
+            {
+                let mut waiters = self.waiters.lock().unwrap();
+
+                for pollfd in fds[1..].iter().filter(|pollfd| pollfd.revents != 0) {
+                    let failure = pollfd.revents & (libc::POLLERR | libc::POLLHUP | libc::POLLNVAL) != 0;
+
+                    if let Some(entry) = waiters.get_mut(&pollfd.fd) {
+                        if failure || pollfd.revents & libc::POLLIN != 0 {
+                            ready.append(&mut entry.read);
+                        }
+                        if failure || pollfd.revents & libc::POLLOUT != 0 {
+                            ready.append(&mut entry.write);
+                        }
+
+                        if entry.read.is_empty() && entry.write.is_empty() {
+                            waiters.remove(&pollfd.fd);
+                        }
+                    }
+                }
+            }
+
+            for (_, waker) in ready.drain(..) {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl Drop for Reactor {
+    ///Closes notification pipe, which happens only if reactor's thread could not be spawned.
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.notify_read);
+            libc::close(self.notify_write);
+        }
+    }
+}
+
+///Socket whose operations are futures, independent of any runtime.
+pub struct AsyncSocket {
+    inner: Socket
+}
+
+impl AsyncSocket {
+    ///Wraps socket, switching it into non-blocking mode.
+    pub fn new(socket: Socket) -> io::Result<AsyncSocket> {
+        socket.set_blocking(false)?;
+
+        Ok(AsyncSocket {
+            inner: socket
+        })
+    }
+
+    ///Returns reference to underlying socket.
+    pub fn get_ref(&self) -> &Socket {
+        &self.inner
+    }
+
+    ///Returns underlying socket.
+    ///
+    ///Note: socket remains in non-blocking mode.
+    pub fn into_inner(self) -> Socket {
+        self.deregister();
+        let socket = unsafe { ptr::read(&self.inner) };
+        mem::forget(self);
+        socket
+    }
+
+    fn deregister(&self) {
+        //Nothing can be registered if reactor hasn't been started.
+        if let Some(reactor) = REACTOR.get() {
+            reactor.deregister(self.inner.as_raw_fd());
+        }
+    }
+
+    ///Schedules wake up of task once socket becomes ready.
+    ///
+    ///If socket became ready in the meantime, reactor's `poll()` reports it right away.
+    fn wait(&self, cx: &mut Context, interest: Interest, registration: &mut Option<u64>) -> io::Result<()> {
+        Reactor::get()?.register(self.inner.as_raw_fd(), interest, cx.waker(), registration)
+    }
+
+    ///Removes waker of future, which no longer waits for socket.
+    fn cancel(&self, interest: Interest, registration: &mut Option<u64>) {
+        if let Some(key) = registration.take() {
+            //Nothing can be registered if reactor hasn't been started.
+            if let Some(reactor) = REACTOR.get() {
+                reactor.unregister(self.inner.as_raw_fd(), interest, key);
+            }
+        }
+    }
+
+    fn poll_io<T, F: FnMut(&Socket) -> Result<T, SocketError>>(&self, cx: &mut Context, interest: Interest, registration: &mut Option<u64>, mut op: F) -> Poll<io::Result<T>> {
+        let result = match op(&self.inner) {
+            Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => match self.wait(cx, interest, registration) {
+                Ok(()) => return Poll::Pending,
+                Err(error) => Err(error)
+            },
+            result => result.map_err(io::Error::from)
+        };
+
+        self.cancel(interest, registration);
+        Poll::Ready(result)
+    }
+
+    ///Connects socket with remote address.
+    ///
+    ///Resolves once connection is established or failed.
+    pub fn connect<'a>(&'a self, addr: &'a net::SocketAddr) -> ConnectFuture<'a> {
+        ConnectFuture {
+            socket: self,
+            addr,
+            in_progress: false,
+            registration: None
+        }
+    }
+
+    ///Accepts new incoming connection.
+    ///
+    ///Accepted socket is non-blocking and not inheritable.
+    pub fn accept(&self) -> AcceptFuture<'_> {
+        AcceptFuture {
+            socket: self,
+            registration: None
+        }
+    }
+
+    ///Receives some bytes from socket.
+    ///
+    ///Resolves into number of received bytes.
+    pub fn recv<'a>(&'a self, buf: &'a mut [u8]) -> RecvFuture<'a> {
+        RecvFuture {
+            socket: self,
+            buf,
+            registration: None
+        }
+    }
+
+    ///Sends some bytes through socket.
+    ///
+    ///Resolves into number of sent bytes.
+    pub fn send<'a>(&'a self, buf: &'a [u8]) -> SendFuture<'a> {
+        SendFuture {
+            socket: self,
+            buf,
+            registration: None
+        }
+    }
+}
+
+impl Drop for AsyncSocket {
+    fn drop(&mut self) {
+        self.deregister();
+    }
+}
+
+impl AsRawFd for AsyncSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+///Future of `AsyncSocket::connect`
+///
+///Dropping it before completion leaves connect in progress, but stops waiting for it.
+pub struct ConnectFuture<'a> {
+    socket: &'a AsyncSocket,
+    addr: &'a net::SocketAddr,
+    in_progress: bool,
+    registration: Option<u64>
+}
+
+impl<'a> ConnectFuture<'a> {
+    fn poll_connect(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        let socket = self.socket;
+
+        if !self.in_progress {
+            match socket.inner.connect(self.addr) {
+                Ok(()) => return Poll::Ready(Ok(())),
+                Err(ref error) if error.raw_os_error() == Some(libc::EINPROGRESS) => self.in_progress = true,
+                Err(error) => return Poll::Ready(Err(error.into()))
+            }
+        }
+
+        //Future might be polled spuriously, so check that connect is actually done.
+        let mut pollfd = libc::pollfd { fd: socket.inner.as_raw_fd(), events: libc::POLLOUT, revents: 0 };
+        match unsafe { libc::poll(&mut pollfd, 1, 0) } {
+            -1 => return Poll::Ready(Err(io::Error::last_os_error())),
+            0 => return match socket.wait(cx, Interest::Write, &mut self.registration) {
+                Ok(()) => Poll::Pending,
+                Err(error) => Poll::Ready(Err(error))
+            },
+            _ => ()
+        }
+
+        Poll::Ready(match socket.inner.take_error() {
+            Ok(None) => Ok(()),
+            Ok(Some(error)) => Err(error.with_addr(*self.addr).into()),
+            Err(error) => Err(error.into())
+        })
+    }
+}
+
+impl<'a> Future for ConnectFuture<'a> {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let result = this.poll_connect(cx);
+
+        if result.is_ready() {
+            this.socket.cancel(Interest::Write, &mut this.registration);
+        }
+
+        result
+    }
+}
+
+impl<'a> Drop for ConnectFuture<'a> {
+    fn drop(&mut self) {
+        self.socket.cancel(Interest::Write, &mut self.registration);
+    }
+}
+
+///Future of `AsyncSocket::accept`
+pub struct AcceptFuture<'a> {
+    socket: &'a AsyncSocket,
+    registration: Option<u64>
+}
+
+impl<'a> Future for AcceptFuture<'a> {
+    type Output = io::Result<(AsyncSocket, net::SocketAddr)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        match this.socket.poll_io(cx, Interest::Read, &mut this.registration, |socket| socket.accept4(NON_BLOCKING | NON_INHERITABLE)) {
+            Poll::Ready(Ok((socket, addr))) => Poll::Ready(Ok((AsyncSocket { inner: socket }, addr))),
+            Poll::Ready(Err(error)) => Poll::Ready(Err(error)),
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+impl<'a> Drop for AcceptFuture<'a> {
+    fn drop(&mut self) {
+        self.socket.cancel(Interest::Read, &mut self.registration);
+    }
+}
+
+///Future of `AsyncSocket::recv`
+pub struct RecvFuture<'a> {
+    socket: &'a AsyncSocket,
+    buf: &'a mut [u8],
+    registration: Option<u64>
+}
+
+impl<'a> Future for RecvFuture<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let buf = &mut *this.buf;
+
+        this.socket.poll_io(cx, Interest::Read, &mut this.registration, |socket| socket.recv(buf, MsgFlags::empty()))
+    }
+}
+
+impl<'a> Drop for RecvFuture<'a> {
+    fn drop(&mut self) {
+        self.socket.cancel(Interest::Read, &mut self.registration);
+    }
+}
+
+///Future of `AsyncSocket::send`
+pub struct SendFuture<'a> {
+    socket: &'a AsyncSocket,
+    buf: &'a [u8],
+    registration: Option<u64>
+}
+
+impl<'a> Future for SendFuture<'a> {
+    type Output = io::Result<usize>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let buf = this.buf;
+
+        this.socket.poll_io(cx, Interest::Write, &mut this.registration, |socket| socket.send(buf, MsgFlags::empty()))
+    }
+}
+
+impl<'a> Drop for SendFuture<'a> {
+    fn drop(&mut self) {
+        self.socket.cancel(Interest::Write, &mut self.registration);
+    }
+}
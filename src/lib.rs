@@ -15,15 +15,15 @@
 //!
 //!use lazy_socket::raw::{
 //!    Socket,
-//!    Family,
+//!    Domain,
+//!    SockType,
 //!    Protocol,
-//!    Type,
 //!    select
 //!};
 //!
 //!fn main() {
 //!    let timeout = 1000;
-//!    let socket = match Socket::new(Family::IPv4, Type::STREAM, Protocol::TCP) {
+//!    let socket = match Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP) {
 //!        Ok(socket) => socket,
 //!        Err(error) => {
 //!            println!("Couldn't open socket. Erro: {}", error);
@@ -41,8 +41,28 @@
 //!    }
 //!}
 //!```
+//!
+//!## Features
+//!
+//!- `safe_buffer_len` - Ensures that buffer's len doesn't overflow on Windows. Enabled by default.
+//!- `future` - Enables `future` module with runtime agnostic `AsyncSocket`. Unix only, requires Rust 1.70.
+//!- `tokio` - Enables `tokio` module with `AsyncSocket`. Unix only.
+//!- `mio` - Implements `mio::event::Source` for `raw::Socket`. Unix only.
+//!- `io-uring` - Enables `uring` module with io_uring backed `Ring`. Linux only.
 
 #[macro_use]
 extern crate bitflags;
+#[cfg(all(unix, feature = "tokio"))]
+extern crate tokio as tokio_crate;
+#[cfg(all(unix, feature = "mio"))]
+extern crate mio;
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+extern crate io_uring;
 
 pub mod raw;
+#[cfg(all(unix, feature = "future"))]
+pub mod future;
+#[cfg(all(unix, feature = "tokio"))]
+pub mod tokio;
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+pub mod uring;
@@ -0,0 +1,193 @@
+use std::net;
+use std::time;
+use std::os::raw::c_int;
+
+use super::{Socket, SocketError, Domain, SockType, Protocol};
+
+///Builder of socket that applies options in the order expected by system.
+///
+///Options that affect address binding (`IPV6_V6ONLY`, `SO_REUSEADDR`, `SO_REUSEPORT`) and buffer sizes
+///are set before `bind()`, so that they take effect for listening and connecting sockets alike.
+///
+///If any step fails socket is closed and error is returned.
+///
+///## Usage:
+///
+///```rust,no_run
+///extern crate lazy_socket;
+///
+///use std::net;
+///use std::str::FromStr;
+///
+///use lazy_socket::raw::{SocketBuilder, Domain, SockType, Protocol};
+///
+///fn main() {
+///    let addr = net::SocketAddr::from_str("[::]:8080").unwrap();
+///    let server = SocketBuilder::new(Domain::IPv6, SockType::STREAM, Protocol::TCP).reuse_address(true)
+///                                                                                 .only_v6(false)
+///                                                                                 .bind(addr)
+///                                                                                 .listen(128)
+///                                                                                 .unwrap();
+///}
+///```
+pub struct SocketBuilder {
+    domain: Domain,
+    ty: SockType,
+    protocol: Protocol,
+    reuse_address: Option<bool>,
+    #[cfg(unix)]
+    reuse_port: Option<bool>,
+    nodelay: Option<bool>,
+    recv_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+    only_v6: Option<bool>,
+    #[cfg(target_os = "linux")]
+    device: Option<String>,
+    read_timeout: Option<time::Duration>,
+    write_timeout: Option<time::Duration>,
+    bind: Option<net::SocketAddr>
+}
+
+impl SocketBuilder {
+    ///Creates new builder with socket's domain, type and protocol.
+    pub fn new(domain: Domain, ty: SockType, protocol: Protocol) -> SocketBuilder {
+        SocketBuilder {
+            domain,
+            ty,
+            protocol,
+            reuse_address: None,
+            #[cfg(unix)]
+            reuse_port: None,
+            nodelay: None,
+            recv_buffer_size: None,
+            send_buffer_size: None,
+            only_v6: None,
+            #[cfg(target_os = "linux")]
+            device: None,
+            read_timeout: None,
+            write_timeout: None,
+            bind: None
+        }
+    }
+
+    ///Sets `SO_REUSEADDR` option.
+    pub fn reuse_address(mut self, value: bool) -> SocketBuilder {
+        self.reuse_address = Some(value);
+        self
+    }
+
+    #[cfg(unix)]
+    ///Sets `SO_REUSEPORT` option.
+    pub fn reuse_port(mut self, value: bool) -> SocketBuilder {
+        self.reuse_port = Some(value);
+        self
+    }
+
+    ///Sets `TCP_NODELAY` option.
+    pub fn nodelay(mut self, value: bool) -> SocketBuilder {
+        self.nodelay = Some(value);
+        self
+    }
+
+    ///Sets size of receive buffer.
+    pub fn recv_buffer_size(mut self, size: usize) -> SocketBuilder {
+        self.recv_buffer_size = Some(size);
+        self
+    }
+
+    ///Sets size of send buffer.
+    pub fn send_buffer_size(mut self, size: usize) -> SocketBuilder {
+        self.send_buffer_size = Some(size);
+        self
+    }
+
+    ///Sets `IPV6_V6ONLY` option.
+    pub fn only_v6(mut self, value: bool) -> SocketBuilder {
+        self.only_v6 = Some(value);
+        self
+    }
+
+    #[cfg(target_os = "linux")]
+    ///Binds socket to network interface.
+    pub fn device(mut self, device: &str) -> SocketBuilder {
+        self.device = Some(device.to_owned());
+        self
+    }
+
+    ///Sets timeout of receive operations.
+    pub fn read_timeout(mut self, timeout: time::Duration) -> SocketBuilder {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    ///Sets timeout of send operations.
+    pub fn write_timeout(mut self, timeout: time::Duration) -> SocketBuilder {
+        self.write_timeout = Some(timeout);
+        self
+    }
+
+    ///Sets address to bind socket to.
+    pub fn bind(mut self, addr: net::SocketAddr) -> SocketBuilder {
+        self.bind = Some(addr);
+        self
+    }
+
+    ///Creates socket, applies options and binds it if address is specified.
+    pub fn build(&self) -> Result<Socket, SocketError> {
+        let socket = Socket::new(self.domain, self.ty, self.protocol)?;
+
+        if let Some(value) = self.only_v6 {
+            socket.set_only_v6(value)?;
+        }
+        if let Some(value) = self.reuse_address {
+            socket.set_reuse_address(value)?;
+        }
+        #[cfg(unix)]
+        {
+            if let Some(value) = self.reuse_port {
+                socket.set_reuse_port(value)?;
+            }
+        }
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(ref device) = self.device {
+                socket.bind_device(device)?;
+            }
+        }
+        //Buffer sizes must be known before connection is established to affect TCP window.
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if let Some(value) = self.nodelay {
+            socket.set_nodelay(value)?;
+        }
+        if let Some(timeout) = self.read_timeout {
+            socket.set_read_timeout(Some(timeout))?;
+        }
+        if let Some(timeout) = self.write_timeout {
+            socket.set_write_timeout(Some(timeout))?;
+        }
+        if let Some(ref addr) = self.bind {
+            socket.bind(addr)?;
+        }
+
+        Ok(socket)
+    }
+
+    ///Builds socket and starts listening for incoming connections.
+    pub fn listen(&self, backlog: c_int) -> Result<Socket, SocketError> {
+        let socket = self.build()?;
+        socket.listen(backlog)?;
+        Ok(socket)
+    }
+
+    ///Builds socket and connects it with remote address.
+    pub fn connect(&self, addr: &net::SocketAddr) -> Result<Socket, SocketError> {
+        let socket = self.build()?;
+        socket.connect(addr)?;
+        Ok(socket)
+    }
+}
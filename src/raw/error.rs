@@ -0,0 +1,263 @@
+use std::io;
+use std::fmt;
+use std::net;
+use std::error::Error;
+use std::os::raw::c_int;
+
+use super::ERROR_NAMES;
+
+///Socket operation, which failed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    ///`socket()`
+    Socket,
+    ///`bind()`
+    Bind,
+    ///`listen()`
+    Listen,
+    ///`connect()`
+    Connect,
+    ///`accept()`
+    Accept,
+    ///`send()`, `sendto()` or `sendmsg()`
+    Send,
+    ///`recv()`, `recvfrom()` or `recvmsg()`
+    Recv,
+    ///`shutdown()`
+    Shutdown,
+    ///`close()`
+    Close,
+    ///`getsockname()`
+    GetName,
+    ///`getpeername()`
+    GetPeerName,
+    ///`getsockopt()` of option `name` at `level`.
+    GetOpt {
+        ///Option level e.g. `SOL_SOCKET`.
+        level: c_int,
+        ///Option name e.g. `SO_ERROR`.
+        name: c_int
+    },
+    ///`setsockopt()` of option `name` at `level`.
+    SetOpt {
+        ///Option level e.g. `SOL_SOCKET`.
+        level: c_int,
+        ///Option name e.g. `SO_REUSEADDR`.
+        name: c_int
+    },
+    ///`ioctl()`
+    Ioctl,
+    ///Manipulation of descriptor flags e.g. `fcntl()`.
+    Fcntl,
+    ///Duplication of socket.
+    Dup,
+    ///`select()` or `poll()`
+    Select,
+    ///Name resolution i.e. `getaddrinfo()`.
+    Resolve,
+    ///`open()`
+    Open
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Operation::Socket => f.write_str("socket"),
+            Operation::Bind => f.write_str("bind"),
+            Operation::Listen => f.write_str("listen"),
+            Operation::Connect => f.write_str("connect"),
+            Operation::Accept => f.write_str("accept"),
+            Operation::Send => f.write_str("send"),
+            Operation::Recv => f.write_str("recv"),
+            Operation::Shutdown => f.write_str("shutdown"),
+            Operation::Close => f.write_str("close"),
+            Operation::GetName => f.write_str("getsockname"),
+            Operation::GetPeerName => f.write_str("getpeername"),
+            Operation::GetOpt { level, name } => write!(f, "getsockopt({}, {})", level, name),
+            Operation::SetOpt { level, name } => write!(f, "setsockopt({}, {})", level, name),
+            Operation::Ioctl => f.write_str("ioctl"),
+            Operation::Fcntl => f.write_str("fcntl"),
+            Operation::Dup => f.write_str("dup"),
+            Operation::Select => f.write_str("select"),
+            Operation::Resolve => f.write_str("getaddrinfo"),
+            Operation::Open => f.write_str("open"),
+        }
+    }
+}
+
+///Classification of socket error.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    ///Operation would block on non-blocking socket.
+    WouldBlock,
+    ///Operation is started, but not completed yet e.g. non-blocking `connect()`.
+    InProgress,
+    ///Interrupted by signal, operation can be retried.
+    Interrupted,
+    ///Connection is reset by peer.
+    PeerReset,
+    ///Connection is refused by peer.
+    ConnectionRefused,
+    ///Connection is aborted before it could be accepted.
+    ConnectionAborted,
+    ///Socket is not connected.
+    NotConnected,
+    ///Socket is shut down or peer closed connection.
+    BrokenPipe,
+    ///Address is already in use.
+    AddrInUse,
+    ///Address is not available on local machine.
+    AddrNotAvailable,
+    ///Insufficient permissions.
+    PermissionDenied,
+    ///Network or host is unreachable.
+    Unreachable,
+    ///Operation timed out.
+    TimedOut,
+    ///Out of descriptors, buffers or memory.
+    Exhausted,
+    ///Invalid argument.
+    InvalidInput,
+    ///Operation, option or protocol is not supported.
+    Unsupported,
+    ///Any other error.
+    Other
+}
+
+impl ErrorClass {
+    fn from_kind(kind: io::ErrorKind) -> ErrorClass {
+        match kind {
+            io::ErrorKind::WouldBlock => ErrorClass::WouldBlock,
+            io::ErrorKind::Interrupted => ErrorClass::Interrupted,
+            io::ErrorKind::ConnectionReset => ErrorClass::PeerReset,
+            io::ErrorKind::ConnectionRefused => ErrorClass::ConnectionRefused,
+            io::ErrorKind::ConnectionAborted => ErrorClass::ConnectionAborted,
+            io::ErrorKind::NotConnected => ErrorClass::NotConnected,
+            io::ErrorKind::BrokenPipe => ErrorClass::BrokenPipe,
+            io::ErrorKind::AddrInUse => ErrorClass::AddrInUse,
+            io::ErrorKind::AddrNotAvailable => ErrorClass::AddrNotAvailable,
+            io::ErrorKind::PermissionDenied => ErrorClass::PermissionDenied,
+            io::ErrorKind::TimedOut => ErrorClass::TimedOut,
+            io::ErrorKind::InvalidInput => ErrorClass::InvalidInput,
+            io::ErrorKind::Unsupported => ErrorClass::Unsupported,
+            _ => ErrorClass::Other
+        }
+    }
+}
+
+///Error of socket operation.
+///
+///Records failed operation, address involved if any, and underlying error,
+///which is displayed as `bind 0.0.0.0:80: EACCES`.
+///
+///It can be converted into `io::Error` with the same kind, keeping `SocketError` as its inner error.
+#[derive(Debug)]
+pub struct SocketError {
+    op: Operation,
+    addr: Option<net::SocketAddr>,
+    error: io::Error
+}
+
+impl SocketError {
+    ///Creates error of `op` from `error`.
+    pub fn new(op: Operation, error: io::Error) -> SocketError {
+        SocketError {
+            op,
+            addr: None,
+            error
+        }
+    }
+
+    ///Creates error of `op` from last OS error i.e. `errno` or `WSAGetLastError()`.
+    pub fn last_os_error(op: Operation) -> SocketError {
+        SocketError::new(op, io::Error::last_os_error())
+    }
+
+    ///Creates error of `op` from OS error `code`.
+    pub fn from_raw_os_error(op: Operation, code: i32) -> SocketError {
+        SocketError::new(op, io::Error::from_raw_os_error(code))
+    }
+
+    pub(crate) fn custom(op: Operation, kind: io::ErrorKind, message: &'static str) -> SocketError {
+        SocketError::new(op, io::Error::new(kind, message))
+    }
+
+    ///Attaches address involved in operation.
+    pub fn with_addr(mut self, addr: net::SocketAddr) -> SocketError {
+        self.addr = Some(addr);
+        self
+    }
+
+    ///Returns failed operation.
+    pub fn operation(&self) -> Operation {
+        self.op
+    }
+
+    ///Returns address involved in operation, if any.
+    pub fn addr(&self) -> Option<net::SocketAddr> {
+        self.addr
+    }
+
+    ///Returns OS error code, if error originates from OS.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        self.error.raw_os_error()
+    }
+
+    ///Returns corresponding `io::ErrorKind`.
+    pub fn kind(&self) -> io::ErrorKind {
+        self.error.kind()
+    }
+
+    ///Returns classification of error.
+    pub fn class(&self) -> ErrorClass {
+        match self.raw_os_error() {
+            Some(code) => match ERROR_NAMES.iter().find(|&&(errno, _, _)| errno == code) {
+                Some(&(_, _, class)) => class,
+                None => ErrorClass::from_kind(self.kind())
+            },
+            None => ErrorClass::from_kind(self.kind())
+        }
+    }
+
+    ///Returns symbolic name of OS error code e.g. `EACCES`, if it is known.
+    pub fn errno_name(&self) -> Option<&'static str> {
+        self.raw_os_error().and_then(|code| ERROR_NAMES.iter().find(|&&(errno, _, _)| errno == code))
+                           .map(|&(_, name, _)| name)
+    }
+
+    ///Unwraps underlying error.
+    pub fn into_inner(self) -> io::Error {
+        self.error
+    }
+}
+
+impl fmt::Display for SocketError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.op)?;
+
+        if let Some(addr) = self.addr {
+            write!(f, " {}", addr)?;
+        }
+
+        match (self.errno_name(), self.raw_os_error()) {
+            (Some(name), _) => write!(f, ": {}", name),
+            (None, Some(code)) => write!(f, ": os error {}", code),
+            (None, None) => write!(f, ": {}", self.error)
+        }
+    }
+}
+
+impl Error for SocketError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+///Error is wrapped as is, so that operation and address are kept in message.
+///
+///OS error code is available through `SocketError`, e.g. `error.get_ref().and_then(|error| error.downcast_ref::<SocketError>())`.
+impl From<SocketError> for io::Error {
+    fn from(error: SocketError) -> io::Error {
+        io::Error::new(error.kind(), error)
+    }
+}
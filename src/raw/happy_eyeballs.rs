@@ -0,0 +1,227 @@
+use std::io;
+use std::cmp;
+use std::net;
+use std::time;
+
+use super::{
+    libc,
+    Socket,
+    Domain,
+    SockType,
+    Protocol,
+    SocketError,
+    Operation,
+    ADDRCONFIG,
+    resolve,
+    duration_to_poll_ms
+};
+
+///Minimal delay between attempts as mandated by RFC 8305.
+const MIN_ATTEMPT_DELAY: time::Duration = time::Duration::from_millis(10);
+
+///Happy Eyeballs (RFC 8305) connector.
+///
+///Races connection attempts to multiple addresses, alternating IPv6 and IPv4.
+///Each next attempt is started once attempt delay passes or previous attempt fails,
+///while earlier attempts are kept running.
+///The first established connection wins and the rest of sockets are closed.
+///
+///This way blackholed address, e.g. IPv6 without working route, delays connection
+///only by attempt delay instead of full TCP timeout.
+///
+///## Usage:
+///
+///```rust,no_run
+///extern crate lazy_socket;
+///
+///use std::time;
+///
+///use lazy_socket::raw::HappyEyeballs;
+///
+///fn main() {
+///    let socket = HappyEyeballs::new().timeout(time::Duration::from_secs(5))
+///                                     .connect_host("example.com:80")
+///                                     .unwrap();
+///}
+///```
+#[derive(Clone, Debug)]
+pub struct HappyEyeballs {
+    attempt_delay: time::Duration,
+    timeout: Option<time::Duration>
+}
+
+impl HappyEyeballs {
+    ///Creates connector with recommended attempt delay of 250ms and no overall timeout.
+    pub fn new() -> HappyEyeballs {
+        HappyEyeballs {
+            attempt_delay: time::Duration::from_millis(250),
+            timeout: None
+        }
+    }
+
+    ///Sets delay before starting next attempt, while previous one is still in progress.
+    ///
+    ///Values below 10ms are raised to 10ms.
+    pub fn attempt_delay(mut self, delay: time::Duration) -> HappyEyeballs {
+        self.attempt_delay = cmp::max(delay, MIN_ATTEMPT_DELAY);
+        self
+    }
+
+    ///Sets overall timeout, after which all attempts are abandoned.
+    ///
+    ///Without it, connector waits until attempts are completed by system.
+    pub fn timeout(mut self, timeout: time::Duration) -> HappyEyeballs {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    ///Resolves `target` of form `host:port` and connects to one of its addresses.
+    ///
+    ///See `Socket::connect_host` for format of `target`.
+    pub fn connect_host(&self, target: &str) -> Result<Socket, SocketError> {
+        let (host, port) = match super::resolve::split_host_port(target) {
+            Some(parts) => parts,
+            None => return Err(SocketError::custom(Operation::Resolve, io::ErrorKind::InvalidInput, "Target must be of form host:port."))
+        };
+
+        let addrs = resolve(Some(host), port, Domain::UNSPECIFIED, SockType::STREAM, ADDRCONFIG)?;
+        self.connect(&addrs)
+    }
+
+    ///Connects TCP socket to one of `addrs`, which are expected in order of preference.
+    ///
+    ///Addresses are reordered to alternate families, starting with family of the first one.
+    ///Established socket is returned in blocking mode.
+    ///
+    ///Returns error of kind `TimedOut` if timeout expires, or error of last failed attempt.
+    ///
+    ///Error may also come from winning attempt, if its socket cannot be switched back into blocking mode.
+    pub fn connect(&self, addrs: &[net::SocketAddr]) -> Result<Socket, SocketError> {
+        let addrs = interleave(addrs);
+        let deadline = self.timeout.map(|timeout| time::Instant::now() + timeout);
+
+        let mut next = addrs.iter();
+        let mut pending: Vec<(Socket, net::SocketAddr)> = Vec::new();
+        let mut next_attempt = time::Instant::now();
+        let mut last_error = None;
+
+        loop {
+            let now = time::Instant::now();
+
+            if let Some(deadline) = deadline {
+                if now >= deadline {
+                    return Err(SocketError::custom(Operation::Connect, io::ErrorKind::TimedOut, "connection timed out"));
+                }
+            }
+
+            if now >= next_attempt {
+                match next.next() {
+                    Some(addr) => match start(addr) {
+                        Ok((socket, false)) => pending.push((socket, *addr)),
+                        //Established right away, which is possible for local addresses.
+                        Ok((socket, true)) => {
+                            socket.set_blocking(true)?;
+                            return Ok(socket);
+                        }
+                        //Failed attempt lets the next one start without waiting.
+                        Err(error) => {
+                            last_error = Some(error);
+                            continue;
+                        }
+                    }
+                    None => if pending.is_empty() {
+                        return Err(last_error.unwrap_or_else(|| SocketError::custom(Operation::Connect, io::ErrorKind::InvalidInput, "No addresses to connect to.")));
+                    }
+                }
+
+                next_attempt = now + self.attempt_delay;
+            }
+
+            //Wake up either for the next attempt or for deadline, whichever is sooner.
+            let mut wait = match next.len() {
+                0 => None,
+                _ => Some(next_attempt.saturating_duration_since(now))
+            };
+            if let Some(deadline) = deadline {
+                let remaining = deadline.saturating_duration_since(now);
+                wait = Some(wait.map_or(remaining, |wait| cmp::min(wait, remaining)));
+            }
+
+            let mut fds: Vec<libc::pollfd> = pending.iter().map(|(socket, _)| libc::pollfd { fd: socket.raw(), events: libc::POLLOUT, revents: 0 }).collect();
+            let timeout_ms = wait.map_or(-1, duration_to_poll_ms);
+
+            if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout_ms) } < 0 {
+                let error = SocketError::last_os_error(Operation::Select);
+                if error.kind() != io::ErrorKind::Interrupted {
+                    return Err(error);
+                }
+                continue;
+            }
+
+            //Iterate in reverse so that removal does not shift unvisited entries.
+            for idx in (0..fds.len()).rev() {
+                if fds[idx].revents == 0 {
+                    continue;
+                }
+
+                let (socket, addr) = pending.remove(idx);
+                //Failure to query outcome fails only this attempt.
+                match socket.take_error() {
+                    Ok(None) => {
+                        socket.set_blocking(true)?;
+                        return Ok(socket);
+                    }
+                    Ok(Some(error)) | Err(error) => {
+                        last_error = Some(error.with_addr(addr));
+                        next_attempt = now;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for HappyEyeballs {
+    fn default() -> HappyEyeballs {
+        HappyEyeballs::new()
+    }
+}
+
+///Reorders addresses to alternate families, keeping relative order within family.
+fn interleave(addrs: &[net::SocketAddr]) -> Vec<net::SocketAddr> {
+    let first_v6 = match addrs.first() {
+        Some(addr) => addr.is_ipv6(),
+        None => return Vec::new()
+    };
+    let (mut preferred, mut other): (Vec<net::SocketAddr>, Vec<net::SocketAddr>) = addrs.iter().partition(|addr| addr.is_ipv6() == first_v6);
+    let mut result = Vec::with_capacity(addrs.len());
+
+    preferred.reverse();
+    other.reverse();
+
+    loop {
+        match (preferred.pop(), other.pop()) {
+            (None, None) => return result,
+            (first, second) => result.extend(first.into_iter().chain(second))
+        }
+    }
+}
+
+///Starts non-blocking connect to `addr`.
+///
+///Returns socket along with whether connection is established immediately.
+fn start(addr: &net::SocketAddr) -> Result<(Socket, bool), SocketError> {
+    let domain = match *addr {
+        net::SocketAddr::V4(_) => Domain::IPv4,
+        net::SocketAddr::V6(_) => Domain::IPv6
+    };
+
+    let socket = Socket::new(domain, SockType::STREAM, Protocol::TCP)?;
+    socket.set_blocking(false)?;
+
+    match socket.connect(addr) {
+        Ok(()) => Ok((socket, true)),
+        Err(ref error) if error.raw_os_error() == Some(libc::EINPROGRESS) => Ok((socket, false)),
+        Err(error) => Err(error)
+    }
+}
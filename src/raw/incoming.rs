@@ -0,0 +1,121 @@
+use std::io;
+use std::net;
+use std::thread;
+use std::time;
+
+#[cfg(unix)]
+use std::fs;
+
+use super::{Socket, SocketError, AcceptFlags, FD_EXHAUSTED_ERRORS};
+#[cfg(unix)]
+use super::{Operation, select};
+#[cfg(windows)]
+use super::{Domain, SockType, Protocol};
+
+#[cfg(unix)]
+type Reserve = fs::File;
+#[cfg(windows)]
+type Reserve = Socket;
+
+#[cfg(unix)]
+///Takes descriptor, which is released once descriptors are exhausted.
+fn reserve() -> Result<Reserve, SocketError> {
+    //Files are opened with `O_CLOEXEC` by std.
+    fs::File::open("/dev/null").map_err(|error| SocketError::new(Operation::Open, error))
+}
+
+#[cfg(windows)]
+///Takes socket handle, which is released once handles are exhausted.
+fn reserve() -> Result<Reserve, SocketError> {
+    Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP)
+}
+
+#[cfg(unix)]
+///Accepts pending connection and closes it right away.
+fn accept_pending(socket: &Socket, flags: AcceptFlags) -> Result<(), SocketError> {
+    if socket.is_blocking()? && select(&[socket], &[], &[], Some(0))? == 0 {
+        return Ok(());
+    }
+
+    socket.accept4(flags).map(drop)
+}
+
+#[cfg(windows)]
+///Accepts pending connection and closes it right away.
+fn accept_pending(socket: &Socket, flags: AcceptFlags) -> Result<(), SocketError> {
+    socket.accept4(flags).map(drop)
+}
+
+///Iterator over incoming connections.
+///
+///Created by `Socket::incoming`.
+///
+///Accept is retried on interruption and on connections aborted before they are accepted.
+///It never returns `None`.
+pub struct Incoming<'a> {
+    socket: &'a Socket,
+    flags: AcceptFlags,
+    backoff: Option<(time::Duration, Option<Reserve>)>
+}
+
+impl<'a> Incoming<'a> {
+    pub(crate) fn new(socket: &'a Socket, flags: AcceptFlags) -> Incoming<'a> {
+        Incoming {
+            socket,
+            flags,
+            backoff: None
+        }
+    }
+
+    ///Enables handling of exhausted descriptors (`EMFILE`/`ENFILE`).
+    ///
+    ///One descriptor is reserved upfront. Once process runs out of descriptors, reserved one
+    ///is released to accept pending connection and close it right away, so that client isn't left hanging.
+    ///Then descriptor is reserved again and accept is retried after `delay`.
+    ///
+    ///Blocking socket is checked for pending connection first, so that shedding doesn't block
+    ///if connection is aborted meanwhile. On Windows mode of socket cannot be queried,
+    ///hence blocking socket might block until next connection.
+    pub fn with_backoff(mut self, delay: time::Duration) -> Result<Incoming<'a>, SocketError> {
+        self.backoff = Some((delay, Some(reserve()?)));
+        Ok(self)
+    }
+
+    fn shed(&mut self) -> Result<(), SocketError> {
+        if let Some((delay, ref mut reserved)) = self.backoff {
+            drop(reserved.take());
+            let result = accept_pending(self.socket, self.flags);
+            *reserved = Some(reserve()?);
+            result?;
+
+            thread::sleep(delay);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Iterator for Incoming<'a> {
+    type Item = Result<(Socket, net::SocketAddr), SocketError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.socket.accept4(self.flags) {
+                Ok(result) => return Some(Ok(result)),
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(ref error) if error.kind() == io::ErrorKind::ConnectionAborted => continue,
+                Err(error) => {
+                    let exhausted = error.raw_os_error().map(|code| FD_EXHAUSTED_ERRORS.contains(&code)).unwrap_or(false);
+
+                    if !exhausted || self.backoff.is_none() {
+                        return Some(Err(error));
+                    }
+
+                    if let Err(error) = self.shed() {
+                        return Some(Err(error));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::io;
+use std::net;
+use std::ptr;
+use std::ffi::CStr;
+
+use super::{libc, InterfaceFlags};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use super::{Socket, Domain, SockType, Protocol};
+
+///Address assigned to network interface.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InterfaceAddr {
+    ///IP address.
+    pub addr: net::IpAddr,
+    ///Length of network prefix in bits, as derived from netmask.
+    pub prefix_len: u8
+}
+
+///Network interface.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Interface {
+    ///Name e.g. `eth0`.
+    pub name: String,
+    ///Index, suitable for `IPV6_MULTICAST_IF` and scope id of IPv6 addresses.
+    pub index: u32,
+    ///Flags.
+    pub flags: InterfaceFlags,
+    ///MTU, if system reports it.
+    pub mtu: Option<u32>,
+    ///Hardware (MAC) address, if interface has one.
+    pub hwaddr: Option<[u8; 6]>,
+    ///IPv4 and IPv6 addresses in order reported by system.
+    pub addrs: Vec<InterfaceAddr>
+}
+
+impl Interface {
+    fn new(name: String, flags: InterfaceFlags) -> Interface {
+        Interface {
+            index: interface_index(&name),
+            name,
+            flags,
+            mtu: None,
+            hwaddr: None,
+            addrs: Vec::new()
+        }
+    }
+}
+
+///Frees list of `getifaddrs()` on drop.
+struct IfAddrs(*mut libc::ifaddrs);
+
+impl Drop for IfAddrs {
+    fn drop(&mut self) {
+        unsafe {
+            libc::freeifaddrs(self.0);
+        }
+    }
+}
+
+fn interface_index(name: &str) -> u32 {
+    //Name comes from C string, so it cannot contain NUL.
+    let mut c_name = name.as_bytes().to_vec();
+    c_name.push(0);
+
+    unsafe { libc::if_nametoindex(c_name.as_ptr() as *const libc::c_char) as u32 }
+}
+
+fn prefix_len(mask: &[u8]) -> u8 {
+    mask.iter().map(|byte| byte.count_ones() as u8).sum()
+}
+
+///Extracts IP address and its netmask.
+unsafe fn ip_addr(addr: *const libc::sockaddr, mask: *const libc::sockaddr) -> Option<InterfaceAddr> {
+    match (*addr).sa_family as libc::c_int {
+        libc::AF_INET => {
+            let ip = net::Ipv4Addr::from((*(addr as *const libc::sockaddr_in)).sin_addr.s_addr.to_ne_bytes());
+            let prefix_len = if mask.is_null() {
+                32
+            } else {
+                prefix_len(&(*(mask as *const libc::sockaddr_in)).sin_addr.s_addr.to_ne_bytes())
+            };
+
+            Some(InterfaceAddr { addr: net::IpAddr::V4(ip), prefix_len })
+        }
+        libc::AF_INET6 => {
+            let ip = net::Ipv6Addr::from((*(addr as *const libc::sockaddr_in6)).sin6_addr.s6_addr);
+            let prefix_len = if mask.is_null() {
+                128
+            } else {
+                prefix_len(&(*(mask as *const libc::sockaddr_in6)).sin6_addr.s6_addr)
+            };
+
+            Some(InterfaceAddr { addr: net::IpAddr::V6(ip), prefix_len })
+        }
+        _ => None
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+///Fills link layer information out of `AF_PACKET` entry.
+unsafe fn link_info(interface: &mut Interface, entry: &libc::ifaddrs) {
+    let addr = &*(entry.ifa_addr as *const libc::sockaddr_ll);
+
+    if addr.sll_halen == 6 {
+        let mut hwaddr = [0; 6];
+        hwaddr.copy_from_slice(&addr.sll_addr[..6]);
+        interface.hwaddr = Some(hwaddr);
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+///Queries MTU of interfaces as `AF_PACKET` entries do not carry it.
+fn fill_mtu(interfaces: &mut [Interface]) {
+    let socket = match Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP) {
+        Ok(socket) => socket,
+        Err(_) => return
+    };
+
+    for interface in interfaces.iter_mut() {
+        interface.mtu = socket.interface_mtu(&interface.name).ok();
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+///Fills link layer information out of `AF_LINK` entry.
+unsafe fn link_info(interface: &mut Interface, entry: &libc::ifaddrs) {
+    let addr = &*(entry.ifa_addr as *const libc::sockaddr_dl);
+
+    if addr.sdl_alen == 6 {
+        //Address follows name within `sdl_data`, which may extend past declared array.
+        let data = std::slice::from_raw_parts(addr.sdl_data.as_ptr() as *const u8, addr.sdl_nlen as usize + 6);
+        let mut hwaddr = [0; 6];
+        hwaddr.copy_from_slice(&data[addr.sdl_nlen as usize..]);
+        interface.hwaddr = Some(hwaddr);
+    }
+
+    if !entry.ifa_data.is_null() {
+        interface.mtu = Some((*(entry.ifa_data as *const libc::if_data)).ifi_mtu);
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const AF_LINK_LAYER: libc::c_int = libc::AF_PACKET;
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const AF_LINK_LAYER: libc::c_int = libc::AF_LINK;
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios")))]
+const AF_LINK_LAYER: libc::c_int = -1;
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios")))]
+unsafe fn link_info(_: &mut Interface, _: &libc::ifaddrs) {
+}
+
+///Lists network interfaces of system using `getifaddrs()`.
+///
+///Each interface is reported once, along with all of its IPv4 and IPv6 addresses.
+///Interfaces follow order in which system reports them.
+///
+///MTU and hardware address are available on Linux, Android, macOS and iOS.
+pub fn interfaces() -> io::Result<Vec<Interface>> {
+    let mut list = ptr::null_mut();
+
+    if unsafe { libc::getifaddrs(&mut list) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let list = IfAddrs(list);
+    let mut result: Vec<Interface> = Vec::new();
+    let mut cursor = list.0;
+
+    while let Some(entry) = unsafe { cursor.as_ref() } {
+        cursor = entry.ifa_next;
+
+        let name = unsafe { CStr::from_ptr(entry.ifa_name) }.to_string_lossy();
+        let position = match result.iter().position(|interface| interface.name == name) {
+            Some(position) => position,
+            None => {
+                let flags = InterfaceFlags::from_bits_truncate(entry.ifa_flags as libc::c_int);
+                result.push(Interface::new(name.into_owned(), flags));
+                result.len() - 1
+            }
+        };
+        let interface = &mut result[position];
+
+        if entry.ifa_addr.is_null() {
+            continue;
+        }
+
+        unsafe {
+            if (*entry.ifa_addr).sa_family as libc::c_int == AF_LINK_LAYER {
+                link_info(interface, entry);
+            } else if let Some(addr) = ip_addr(entry.ifa_addr, entry.ifa_netmask) {
+                interface.addrs.push(addr);
+            }
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fill_mtu(&mut result);
+
+    Ok(result)
+}
@@ -1,12 +1,50 @@
-//! Raw module.
-//!
-//! Core part that exposes Raw Socket.
-#[cfg(unix)]
-mod unix;
-#[cfg(unix)]
-pub use self::unix::*;
-
-#[cfg(windows)]
-mod windows;
-#[cfg(windows)]
-pub use self::windows::*;
+//! Raw module.
+//!
+//! Core part that exposes Raw Socket.
+#[macro_use]
+mod types;
+pub use self::types::{Domain, SockType, Protocol};
+
+mod error;
+pub use self::error::{SocketError, Operation, ErrorClass};
+
+#[cfg(unix)]
+mod unix;
+#[cfg(unix)]
+pub use self::unix::*;
+#[cfg(unix)]
+pub(crate) use self::unix::libc;
+
+#[cfg(unix)]
+mod interfaces;
+#[cfg(unix)]
+pub use self::interfaces::{interfaces, Interface, InterfaceAddr};
+
+#[cfg(unix)]
+mod resolve;
+#[cfg(unix)]
+pub use self::resolve::resolve;
+
+#[cfg(unix)]
+mod happy_eyeballs;
+#[cfg(unix)]
+pub use self::happy_eyeballs::HappyEyeballs;
+
+#[cfg(windows)]
+mod windows;
+#[cfg(windows)]
+pub use self::windows::*;
+
+mod shutdown;
+pub use self::shutdown::ShutdownOnDrop;
+
+mod socket_ref;
+pub use self::socket_ref::SocketRef;
+
+mod builder;
+pub use self::builder::SocketBuilder;
+
+mod incoming;
+pub use self::incoming::Incoming;
+
+mod stream;
@@ -0,0 +1,163 @@
+use std::io;
+use std::mem;
+use std::net;
+use std::ptr;
+use std::cmp;
+use std::ffi::{CStr, CString};
+
+use super::{
+    libc,
+    Socket,
+    Domain,
+    SockType,
+    Protocol,
+    ResolveFlags,
+    ADDRCONFIG,
+    SocketError,
+    Operation,
+    sockaddr_to_addr
+};
+
+///Frees list of `getaddrinfo()` on drop.
+struct AddrInfo(*mut libc::addrinfo);
+
+impl Drop for AddrInfo {
+    fn drop(&mut self) {
+        unsafe {
+            libc::freeaddrinfo(self.0);
+        }
+    }
+}
+
+fn resolve_error(code: libc::c_int, host: Option<&str>) -> SocketError {
+    if code == libc::EAI_SYSTEM {
+        return SocketError::last_os_error(Operation::Resolve);
+    }
+
+    let kind = match code {
+        libc::EAI_NONAME => io::ErrorKind::NotFound,
+        libc::EAI_FAMILY | libc::EAI_SOCKTYPE | libc::EAI_BADFLAGS | libc::EAI_SERVICE => io::ErrorKind::InvalidInput,
+        _ => io::ErrorKind::Other
+    };
+    let message = unsafe { CStr::from_ptr(libc::gai_strerror(code)) }.to_string_lossy();
+    let message = match host {
+        Some(host) => format!("{}: {}", host, message),
+        None => message.into_owned()
+    };
+
+    SocketError::new(Operation::Resolve, io::Error::new(kind, message))
+}
+
+///Resolves `host` into addresses with `port` using `getaddrinfo()`.
+///
+///Parameters:
+///
+///- `host` - Name or numeric address. When `None`, wildcard address is returned with `PASSIVE` or loopback otherwise.
+///- `port` - Port to set in each address.
+///- `family` - `Domain::IPv4`, `Domain::IPv6` or `Domain::UNSPECIFIED` for both.
+///- `ty` - Type of socket, addresses are intended for.
+///- `flags` - Resolution flags, e.g. `ADDRCONFIG` to skip families not configured on local system.
+///
+///Addresses are returned in order of preference according to system configuration, without duplicates.
+pub fn resolve(host: Option<&str>, port: u16, family: Domain, ty: SockType, flags: ResolveFlags) -> Result<Vec<net::SocketAddr>, SocketError> {
+    let c_host = match host {
+        Some(host) => match CString::new(host) {
+            Ok(host) => Some(host),
+            Err(_) => return Err(SocketError::custom(Operation::Resolve, io::ErrorKind::InvalidInput, "Host contains NUL byte."))
+        }
+        None => None
+    };
+    let c_port = CString::new(port.to_string()).expect("Port cannot contain NUL");
+
+    let mut hints: libc::addrinfo = unsafe { mem::zeroed() };
+    hints.ai_flags = flags.bits() | libc::AI_NUMERICSERV;
+    hints.ai_family = family.into();
+    hints.ai_socktype = ty.into();
+
+    let mut list = ptr::null_mut();
+    let result = unsafe {
+        libc::getaddrinfo(c_host.as_ref().map_or(ptr::null(), |host| host.as_ptr()), c_port.as_ptr(), &hints, &mut list)
+    };
+
+    if result != 0 {
+        return Err(resolve_error(result, host));
+    }
+
+    let list = AddrInfo(list);
+    let mut addrs: Vec<net::SocketAddr> = Vec::new();
+    let mut cursor = list.0;
+
+    while let Some(entry) = unsafe { cursor.as_ref() } {
+        cursor = entry.ai_next;
+
+        if entry.ai_addr.is_null() {
+            continue;
+        }
+
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let len = cmp::min(entry.ai_addrlen as usize, mem::size_of::<libc::sockaddr_storage>());
+        unsafe {
+            ptr::copy_nonoverlapping(entry.ai_addr as *const u8, &mut storage as *mut _ as *mut u8, len);
+        }
+
+        //Other families cannot be requested, but system may return them anyway.
+        if let Ok(addr) = sockaddr_to_addr(&storage, len as libc::socklen_t) {
+            if !addrs.contains(&addr) {
+                addrs.push(addr);
+            }
+        }
+    }
+
+    Ok(addrs)
+}
+
+///Splits `host:port` or `[host]:port` into its parts.
+pub(crate) fn split_host_port(target: &str) -> Option<(&str, u16)> {
+    let (host, port) = target.rsplit_once(':')?;
+
+    let host = match host.strip_prefix('[') {
+        Some(host) => host.strip_suffix(']')?,
+        //IPv6 address must be enclosed into brackets.
+        None if host.contains(':') => return None,
+        None => host
+    };
+
+    if host.is_empty() {
+        None
+    } else {
+        port.parse().ok().map(|port| (host, port))
+    }
+}
+
+impl Socket {
+    ///Resolves `target` of form `host:port` and connects TCP socket to it.
+    ///
+    ///IPv6 addresses must be enclosed into brackets, e.g. `[::1]:443`.
+    ///
+    ///Resolved addresses are tried in turn, creating new socket for each, until one connects.
+    ///Returns error of last attempt if none does.
+    pub fn connect_host(target: &str) -> Result<Socket, SocketError> {
+        let (host, port) = match split_host_port(target) {
+            Some(parts) => parts,
+            None => return Err(SocketError::custom(Operation::Resolve, io::ErrorKind::InvalidInput, "Target must be of form host:port."))
+        };
+
+        let addrs = resolve(Some(host), port, Domain::UNSPECIFIED, SockType::STREAM, ADDRCONFIG)?;
+        let mut last_error = None;
+
+        for addr in addrs {
+            let domain = match addr {
+                net::SocketAddr::V4(_) => Domain::IPv4,
+                net::SocketAddr::V6(_) => Domain::IPv6
+            };
+
+            let result = Socket::new(domain, SockType::STREAM, Protocol::TCP).and_then(|socket| socket.connect(&addr).map(|_| socket));
+            match result {
+                Ok(socket) => return Ok(socket),
+                Err(error) => last_error = Some(error)
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| resolve_error(libc::EAI_NONAME, Some(host))))
+    }
+}
@@ -0,0 +1,52 @@
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+
+use super::{Socket, ShutdownType};
+
+///Socket that is shut down before being closed on `Drop`.
+///
+///Plain `Socket` only closes its descriptor when dropped, which leaves connection intact for
+///anyone else sharing it (e.g. forked child or duplicated descriptor).
+///Wrap socket into `ShutdownOnDrop` when connection should be torn down for every holder instead.
+pub struct ShutdownOnDrop {
+    inner: Socket,
+    direction: ShutdownType
+}
+
+impl ShutdownOnDrop {
+    ///Wraps socket, shutting down `direction` on drop.
+    pub fn new(socket: Socket, direction: ShutdownType) -> ShutdownOnDrop {
+        ShutdownOnDrop {
+            inner: socket,
+            direction
+        }
+    }
+
+    ///Returns underlying socket without shutting it down.
+    pub fn into_inner(self) -> Socket {
+        let socket = unsafe { ptr::read(&self.inner) };
+        mem::forget(self);
+        socket
+    }
+}
+
+impl Deref for ShutdownOnDrop {
+    type Target = Socket;
+
+    fn deref(&self) -> &Socket {
+        &self.inner
+    }
+}
+
+impl DerefMut for ShutdownOnDrop {
+    fn deref_mut(&mut self) -> &mut Socket {
+        &mut self.inner
+    }
+}
+
+impl Drop for ShutdownOnDrop {
+    fn drop(&mut self) {
+        let _ = self.inner.shutdown(self.direction);
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+
+#[cfg(unix)]
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd};
+#[cfg(windows)]
+use std::os::windows::io::{AsSocket, AsRawSocket, BorrowedSocket, FromRawSocket};
+
+use super::Socket;
+
+///Borrowed socket.
+///
+///Allows to use `Socket` API over descriptor owned by someone else (e.g. `std::net::TcpStream`).
+///Socket is never closed by `SocketRef`.
+///
+///## Usage:
+///
+///```rust,no_run
+///extern crate lazy_socket;
+///
+///use std::net::TcpStream;
+///use lazy_socket::raw::{SocketRef, select};
+///
+///fn main() {
+///    let stream = TcpStream::connect("127.0.0.1:80").unwrap();
+///    let socket = SocketRef::from(&stream);
+///
+///    let _ = select(&[&socket], &[], &[], Some(1000));
+///}
+///```
+pub struct SocketRef<'a> {
+    inner: ManuallyDrop<Socket>,
+    _lifetime: PhantomData<&'a Socket>
+}
+
+impl<'a> Deref for SocketRef<'a> {
+    type Target = Socket;
+
+    fn deref(&self) -> &Socket {
+        &self.inner
+    }
+}
+
+impl<'a> fmt::Debug for SocketRef<'a> {
+    #[cfg(unix)]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SocketRef").field("fd", &self.inner.as_raw_fd()).finish()
+    }
+
+    #[cfg(windows)]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SocketRef").field("socket", &self.inner.as_raw_socket()).finish()
+    }
+}
+
+#[cfg(unix)]
+impl<'a> From<BorrowedFd<'a>> for SocketRef<'a> {
+    fn from(fd: BorrowedFd<'a>) -> SocketRef<'a> {
+        SocketRef {
+            inner: ManuallyDrop::new(unsafe { Socket::from_raw_fd(fd.as_raw_fd()) }),
+            _lifetime: PhantomData
+        }
+    }
+}
+
+#[cfg(unix)]
+impl<'a, T: AsFd> From<&'a T> for SocketRef<'a> {
+    fn from(value: &'a T) -> SocketRef<'a> {
+        SocketRef::from(value.as_fd())
+    }
+}
+
+#[cfg(unix)]
+impl<'a> AsFd for SocketRef<'a> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+
+#[cfg(windows)]
+impl<'a> From<BorrowedSocket<'a>> for SocketRef<'a> {
+    fn from(socket: BorrowedSocket<'a>) -> SocketRef<'a> {
+        SocketRef {
+            inner: ManuallyDrop::new(unsafe { Socket::from_raw_socket(socket.as_raw_socket()) }),
+            _lifetime: PhantomData
+        }
+    }
+}
+
+#[cfg(windows)]
+impl<'a, T: AsSocket> From<&'a T> for SocketRef<'a> {
+    fn from(value: &'a T) -> SocketRef<'a> {
+        SocketRef::from(value.as_socket())
+    }
+}
+
+#[cfg(windows)]
+impl<'a> AsSocket for SocketRef<'a> {
+    fn as_socket(&self) -> BorrowedSocket<'_> {
+        self.inner.as_socket()
+    }
+}
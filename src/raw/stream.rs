@@ -0,0 +1,169 @@
+use std::io;
+use std::cmp;
+
+use std::net;
+
+use super::{Socket, MsgFlags, PEEK, OOB, SocketError, Operation};
+
+///Retries operation that got interrupted by signal.
+fn retry<T, F: FnMut() -> Result<T, SocketError>>(mut op: F) -> io::Result<T> {
+    loop {
+        match op() {
+            Err(ref error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            result => return result.map_err(io::Error::from)
+        }
+    }
+}
+
+impl Socket {
+    ///Receives some bytes without removing them from queue.
+    pub fn peek(&self, buf: &mut [u8]) -> Result<usize, SocketError> {
+        self.recv(buf, PEEK)
+    }
+
+    ///Receives some bytes without removing them from queue, along with remote address.
+    pub fn peek_from(&self, buf: &mut [u8]) -> Result<(usize, net::SocketAddr), SocketError> {
+        self.recv_from(buf, PEEK)
+    }
+
+    ///Receives out-of-band (urgent) data.
+    ///
+    ///Use `at_mark` to find out when all data preceding it is received.
+    pub fn recv_oob(&self, buf: &mut [u8]) -> Result<usize, SocketError> {
+        self.recv(buf, OOB)
+    }
+
+    ///Sends out-of-band (urgent) data.
+    ///
+    ///Note: TCP delivers only last byte out-of-band, preceding bytes are sent as regular data.
+    pub fn send_oob(&self, buf: &[u8]) -> Result<usize, SocketError> {
+        self.send(buf, OOB)
+    }
+
+    ///Sends whole buffer, retrying on partial sends and interruptions.
+    ///
+    ///Fails with `WriteZero` if system accepts no bytes.
+    pub fn send_all(&self, mut buf: &[u8]) -> Result<(), SocketError> {
+        while !buf.is_empty() {
+            match self.send(buf, MsgFlags::empty()) {
+                Ok(0) => return Err(SocketError::custom(Operation::Send, io::ErrorKind::WriteZero, "failed to send whole buffer")),
+                Ok(len) => buf = &buf[len..],
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => (),
+                Err(error) => return Err(error)
+            }
+        }
+
+        Ok(())
+    }
+
+    ///Receives exactly enough bytes to fill buffer, retrying on partial receives and interruptions.
+    ///
+    ///Fails with `UnexpectedEof` if connection is closed before buffer is filled.
+    pub fn recv_exact(&self, mut buf: &mut [u8]) -> Result<(), SocketError> {
+        while !buf.is_empty() {
+            match self.recv(buf, MsgFlags::empty()) {
+                Ok(0) => return Err(SocketError::custom(Operation::Recv, io::ErrorKind::UnexpectedEof, "failed to fill whole buffer")),
+                Ok(len) => buf = &mut buf[len..],
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => (),
+                Err(error) => return Err(error)
+            }
+        }
+
+        Ok(())
+    }
+
+    ///Receives bytes until `delim` is encountered, reading no more than `max` bytes.
+    ///
+    ///Returned bytes include delimiter, unless connection is closed before it is received.
+    ///Nothing past delimiter is consumed, as incoming data is inspected with `PEEK` first.
+    ///
+    ///Fails with `InvalidData` if there is no delimiter within `max` bytes, which are consumed regardless.
+    ///
+    ///Intended for stream sockets.
+    pub fn recv_until(&self, delim: u8, max: usize) -> Result<Vec<u8>, SocketError> {
+        let mut result = Vec::new();
+        let mut chunk = [0u8; 512];
+
+        while result.len() < max {
+            let len = cmp::min(chunk.len(), max - result.len());
+            let len = match self.recv(&mut chunk[..len], PEEK) {
+                Ok(0) => return Ok(result),
+                Ok(len) => len,
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error)
+            };
+
+            let len = match chunk[..len].iter().position(|&byte| byte == delim) {
+                Some(pos) => pos + 1,
+                None => len
+            };
+
+            let len = loop {
+                match self.recv(&mut chunk[..len], MsgFlags::empty()) {
+                    Ok(len) => break len,
+                    Err(ref error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(error) => return Err(error)
+                }
+            };
+            result.extend_from_slice(&chunk[..len]);
+
+            if result.last() == Some(&delim) {
+                return Ok(result);
+            }
+        }
+
+        Err(SocketError::custom(Operation::Recv, io::ErrorKind::InvalidData, "delimiter is not found within limit"))
+    }
+}
+
+///Reads through `recv()` without flags.
+impl io::Read for &Socket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        retry(|| self.recv(buf, MsgFlags::empty()))
+    }
+
+    #[cfg(unix)]
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut]) -> io::Result<usize> {
+        retry(|| self.recv_msg(bufs, &mut [], MsgFlags::empty()).map(|msg| msg.len))
+    }
+}
+
+///Writes through `send()` without flags.
+impl io::Write for &Socket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        retry(|| self.send(buf, MsgFlags::empty()))
+    }
+
+    #[cfg(unix)]
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        retry(|| self.send_vectored(bufs, MsgFlags::empty()))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl io::Read for Socket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (&*self).read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut]) -> io::Result<usize> {
+        (&*self).read_vectored(bufs)
+    }
+}
+
+impl io::Write for Socket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        (&*self).write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::os::raw::c_int;
+
+macro_rules! impl_newtype {
+    ($($t:ident),+) => {
+        $(
+            impl From<c_int> for $t {
+                fn from(value: c_int) -> $t {
+                    $t(value)
+                }
+            }
+
+            impl From<$t> for c_int {
+                fn from(value: $t) -> c_int {
+                    value.0
+                }
+            }
+        )+
+    };
+}
+
+///Declares named constants of newtype along with lookup of their names.
+macro_rules! impl_constants {
+    ($t:ident { $($(#[cfg($cfg:meta)])* $name:ident = $value:expr => $display:expr;)+ }) => {
+        #[allow(non_upper_case_globals)]
+        impl $t {
+            $(
+                $(#[cfg($cfg)])*
+                pub const $name: $t = $t($value);
+            )+
+
+            pub(crate) fn name(&self) -> Option<&'static str> {
+                $(
+                    $(#[cfg($cfg)])*
+                    {
+                        if self.0 == $value {
+                            return Some($display);
+                        }
+                    }
+                )+
+
+                None
+            }
+        }
+    };
+}
+
+///Socket domain i.e. address family.
+///
+///Unknown domains can be created from `c_int`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Domain(pub(crate) c_int);
+
+///Socket type.
+///
+///Unknown types can be created from `c_int`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SockType(pub(crate) c_int);
+
+///Socket protocol.
+///
+///Unknown protocols can be created from `c_int`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Protocol(pub(crate) c_int);
+
+impl_newtype!(Domain, SockType, Protocol);
+
+impl fmt::Display for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.0)
+        }
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{}", self.0)
+        }
+    }
+}
+
+impl fmt::Display for SockType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let flags = SockType::FLAGS.iter().fold(0, |acc, &(flag, _)| acc | flag);
+        let base = SockType(self.0 & !flags);
+
+        match base.name() {
+            Some(name) => f.write_str(name)?,
+            None => write!(f, "{}", base.0)?
+        }
+
+        for &(flag, name) in SockType::FLAGS.iter() {
+            if self.0 & flag != 0 {
+                write!(f, " | {}", name)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -3,28 +3,30 @@ use std::io;
 use std::mem;
 use std::ptr;
 use std::cmp;
+use std::time;
+use std::convert::TryFrom;
+use std::os::unix::net as unix_net;
 
-mod libc {
+pub(crate) mod libc {
     extern crate libc;
 
     //Types
     pub use self::libc::{
         c_int,
-        c_void,
         c_char,
-        c_long,
+        c_void,
         c_ulong,
-        ssize_t,
         socklen_t,
-        size_t,
-        sockaddr,
         sockaddr_storage,
         sa_family_t,
-        in_port_t,
+        msghdr,
+        iovec,
         fd_set,
         timeval,
         time_t,
-        suseconds_t
+        suseconds_t,
+        pollfd,
+        nfds_t
     };
 
     #[cfg(target_env = "musl")]
@@ -35,24 +37,208 @@ mod libc {
     pub use self::libc::{
         sockaddr_in,
         sockaddr_in6,
-
-        in_addr,
-        in6_addr
     };
 
+    #[allow(clippy::upper_case_acronyms)]
     pub type SOCKET = c_int;
     pub const SOCKET_ERROR: c_int = -1;
-    pub const SOCKET_SHUTDOWN: c_int = libc::ESHUTDOWN;
 
     //Constants
     pub use self::libc::{
-        EINVAL,
         FIONBIO,
+        FIONREAD,
         F_GETFD,
         F_SETFD,
-        FD_CLOEXEC
+        F_GETFL,
+        F_DUPFD_CLOEXEC,
+        FD_CLOEXEC,
+        O_NONBLOCK,
+        EAGAIN,
+        EWOULDBLOCK,
+        EINPROGRESS,
+        EALREADY,
+        EINTR,
+        ECONNRESET,
+        ECONNREFUSED,
+        ECONNABORTED,
+        ENETRESET,
+        ENOTCONN,
+        EISCONN,
+        EPIPE,
+        ESHUTDOWN,
+        EADDRINUSE,
+        EADDRNOTAVAIL,
+        EACCES,
+        EPERM,
+        ENETUNREACH,
+        ENETDOWN,
+        EHOSTUNREACH,
+        EHOSTDOWN,
+        ETIMEDOUT,
+        EMFILE,
+        ENFILE,
+        ENOBUFS,
+        ENOMEM,
+        EINVAL,
+        EBADF,
+        EFAULT,
+        ENOTSOCK,
+        EMSGSIZE,
+        EDESTADDRREQ,
+        EAFNOSUPPORT,
+        EPROTONOSUPPORT,
+        EPROTOTYPE,
+        ENOPROTOOPT,
+        EOPNOTSUPP,
+        POLLOUT,
+        SOL_SOCKET,
+        FD_SETSIZE,
+        SO_ERROR,
+        SO_TYPE,
+        SO_ACCEPTCONN,
+        SO_REUSEADDR,
+        SO_REUSEPORT,
+        SO_RCVBUF,
+        SO_SNDBUF,
+        SO_RCVTIMEO,
+        SO_SNDTIMEO,
+        IPPROTO_TCP,
+        IPPROTO_IPV6,
+        TCP_NODELAY,
+        IPV6_V6ONLY
+    };
+
+    #[cfg(target_os = "linux")]
+    pub use self::libc::SO_BINDTODEVICE;
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub use self::libc::TIOCOUTQ;
+
+    pub use self::libc::{
+        IFF_UP,
+        IFF_BROADCAST,
+        IFF_LOOPBACK,
+        IFF_POINTOPOINT,
+        IFF_RUNNING,
+        IFF_NOARP,
+        IFF_PROMISC,
+        IFF_MULTICAST,
+    };
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub use self::libc::{
+        ifreq,
+        SIOCGIFINDEX,
+        SIOCGIFMTU,
+        SIOCGIFHWADDR,
+        SIOCGIFFLAGS
+    };
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub use self::libc::SO_NWRITE;
+
+    pub use self::libc::{
+        addrinfo,
+        getaddrinfo,
+        freeaddrinfo,
+        gai_strerror,
+        AI_PASSIVE,
+        AI_ADDRCONFIG,
+        AI_NUMERICHOST,
+        AI_NUMERICSERV,
+        EAI_SYSTEM,
+        EAI_NONAME,
+        EAI_FAMILY,
+        EAI_SOCKTYPE,
+        EAI_BADFLAGS,
+        EAI_SERVICE
+    };
+
+    pub use self::libc::{
+        sockaddr,
+        ifaddrs,
+        getifaddrs,
+        freeifaddrs,
+        if_nametoindex
+    };
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub use self::libc::sockaddr_ll;
+    #[cfg(target_os = "android")]
+    pub use self::libc::AF_PACKET;
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub use self::libc::{
+        AF_LINK,
+        sockaddr_dl,
+        if_data
+    };
+
+    //Used by reactor of `future` module.
+    #[cfg(feature = "future")]
+    pub use self::libc::{
+        read,
+        write,
+        O_CLOEXEC,
+        POLLIN,
+        POLLERR,
+        POLLHUP,
+        POLLNVAL,
+        EIO
+    };
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    pub use self::libc::{
+        sysconf,
+        _SC_PAGESIZE
+    };
+
+    #[cfg(all(feature = "future", any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd")))]
+    pub use self::libc::pipe2;
+    #[cfg(all(feature = "future", not(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "dragonfly", target_os = "openbsd", target_os = "netbsd"))))]
+    pub use self::libc::{
+        pipe,
+        F_SETFL
+    };
+
+    //POSIX function, which is not bound by libc crate.
+    extern "C" {
+        pub fn sockatmark(fd: c_int) -> c_int;
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    pub use self::libc::SO_DOMAIN;
+
+    pub use self::libc::{
+        MSG_PEEK,
+        MSG_DONTWAIT,
+        MSG_WAITALL,
+        MSG_TRUNC,
+        MSG_CTRUNC,
+        MSG_EOR,
+        MSG_OOB,
+    };
+
+    #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+    pub use self::libc::MSG_NOSIGNAL;
+    //Apple platforms rely on `SO_NOSIGPIPE` instead.
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const MSG_NOSIGNAL: c_int = 0;
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub use self::libc::SO_NOSIGPIPE;
+
+    #[cfg(target_os = "linux")]
+    pub use self::libc::{
+        MSG_MORE,
+        MSG_CONFIRM,
+        MSG_ERRQUEUE,
+        MSG_ZEROCOPY,
+        MSG_FASTOPEN,
     };
 
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    pub use self::libc::MSG_CMSG_CLOEXEC;
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+    pub const MSG_CMSG_CLOEXEC: c_int = 0;
+
     #[cfg(target_os = "macos")]
     pub use self::libc::{
         AF_UNIX,
@@ -66,6 +252,8 @@ mod libc {
 
     #[cfg(target_os = "macos")]
     pub const AF_UNSPEC: c_int = 0;
+    //Darwin has no such type flags, values are borrowed from Linux.
+    //They only back `AcceptFlags`, which are always emulated, and must never reach the OS.
     #[cfg(target_os = "macos")]
     pub const SOCK_NONBLOCK: c_int = 0o0004000;
     #[cfg(target_os = "macos")]
@@ -89,97 +277,263 @@ mod libc {
     pub use self::libc::{
         AF_NETLINK,
         AF_PACKET,
+        AF_VSOCK,
+        AF_ALG,
+        AF_TIPC,
+        IPPROTO_MPTCP
     };
 
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "freebsd", target_os = "netbsd"))]
+    pub use self::libc::IPPROTO_SCTP;
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    pub use self::libc::IPPROTO_UDPLITE;
+    pub use self::libc::IPPROTO_RAW;
+
     //Functions
     pub use self::libc::{
         socket,
         getsockname,
+        getpeername,
         bind,
         listen,
         recv,
         recvfrom,
+        recvmsg,
         send,
         sendto,
-        accept,
+        sendmsg,
         connect,
         getsockopt,
         setsockopt,
         fcntl,
+        dup2,
         ioctl,
         shutdown,
         close,
         select,
+        poll,
         FD_SET
     };
 
-    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "dragonflybsd"))]
+    #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "dragonfly"))]
     pub use self::libc::{
         accept4
     };
+
+    #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "dragonfly")))]
+    pub use self::libc::{
+        accept
+    };
 }
 
 use self::libc::*;
+use super::{Domain, SockType, Protocol, Incoming, SocketError, Operation, ErrorClass};
+
+pub(crate) const FD_EXHAUSTED_ERRORS: &[c_int] = &[EMFILE, ENFILE];
+
+///Names and classes of known `errno` values.
+pub(crate) const ERROR_NAMES: &[(c_int, &str, ErrorClass)] = &[
+    (EAGAIN, "EAGAIN", ErrorClass::WouldBlock),
+    (EWOULDBLOCK, "EWOULDBLOCK", ErrorClass::WouldBlock),
+    (EINPROGRESS, "EINPROGRESS", ErrorClass::InProgress),
+    (EALREADY, "EALREADY", ErrorClass::InProgress),
+    (EINTR, "EINTR", ErrorClass::Interrupted),
+    (ECONNRESET, "ECONNRESET", ErrorClass::PeerReset),
+    (ENETRESET, "ENETRESET", ErrorClass::PeerReset),
+    (ECONNREFUSED, "ECONNREFUSED", ErrorClass::ConnectionRefused),
+    (ECONNABORTED, "ECONNABORTED", ErrorClass::ConnectionAborted),
+    (ENOTCONN, "ENOTCONN", ErrorClass::NotConnected),
+    (EPIPE, "EPIPE", ErrorClass::BrokenPipe),
+    (ESHUTDOWN, "ESHUTDOWN", ErrorClass::BrokenPipe),
+    (EADDRINUSE, "EADDRINUSE", ErrorClass::AddrInUse),
+    (EADDRNOTAVAIL, "EADDRNOTAVAIL", ErrorClass::AddrNotAvailable),
+    (EACCES, "EACCES", ErrorClass::PermissionDenied),
+    (EPERM, "EPERM", ErrorClass::PermissionDenied),
+    (ENETUNREACH, "ENETUNREACH", ErrorClass::Unreachable),
+    (ENETDOWN, "ENETDOWN", ErrorClass::Unreachable),
+    (EHOSTUNREACH, "EHOSTUNREACH", ErrorClass::Unreachable),
+    (EHOSTDOWN, "EHOSTDOWN", ErrorClass::Unreachable),
+    (ETIMEDOUT, "ETIMEDOUT", ErrorClass::TimedOut),
+    (EMFILE, "EMFILE", ErrorClass::Exhausted),
+    (ENFILE, "ENFILE", ErrorClass::Exhausted),
+    (ENOBUFS, "ENOBUFS", ErrorClass::Exhausted),
+    (ENOMEM, "ENOMEM", ErrorClass::Exhausted),
+    (EINVAL, "EINVAL", ErrorClass::InvalidInput),
+    (EBADF, "EBADF", ErrorClass::InvalidInput),
+    (EFAULT, "EFAULT", ErrorClass::InvalidInput),
+    (ENOTSOCK, "ENOTSOCK", ErrorClass::InvalidInput),
+    (EISCONN, "EISCONN", ErrorClass::InvalidInput),
+    (EMSGSIZE, "EMSGSIZE", ErrorClass::InvalidInput),
+    (EDESTADDRREQ, "EDESTADDRREQ", ErrorClass::InvalidInput),
+    (EAFNOSUPPORT, "EAFNOSUPPORT", ErrorClass::Unsupported),
+    (EPROTONOSUPPORT, "EPROTONOSUPPORT", ErrorClass::Unsupported),
+    (EPROTOTYPE, "EPROTOTYPE", ErrorClass::Unsupported),
+    (ENOPROTOOPT, "ENOPROTOOPT", ErrorClass::Unsupported),
+    (EOPNOTSUPP, "EOPNOTSUPP", ErrorClass::Unsupported),
+];
 
 macro_rules! impl_into_trait {
     ($($t:ty), +) => {
         $(
-            impl Into<c_int> for $t {
-                fn into(self) -> c_int {
-                    self as c_int
+            impl From<$t> for c_int {
+                fn from(value: $t) -> c_int {
+                    value as c_int
                 }
             }
         )+
     };
 }
 
-#[allow(non_snake_case, non_upper_case_globals)]
-///Socket family
-pub mod Family {
-    use super::libc::*;
-    pub const UNSPECIFIED: c_int = AF_UNSPEC;
-    pub const UNIX: c_int = AF_UNIX;
-    pub const IPv4: c_int = AF_INET;
-    pub const IPv6: c_int = AF_INET6;
+impl_constants!(Domain {
+    UNSPECIFIED = AF_UNSPEC => "AF_UNSPEC";
+    UNIX = AF_UNIX => "AF_UNIX";
+    IPv4 = AF_INET => "AF_INET";
+    IPv6 = AF_INET6 => "AF_INET6";
     #[cfg(target_os = "linux")]
-    pub const NETLINK: c_int = AF_NETLINK;
+    NETLINK = AF_NETLINK => "AF_NETLINK";
     #[cfg(target_os = "linux")]
-    pub const PACKET: c_int = AF_PACKET;
-}
+    PACKET = AF_PACKET => "AF_PACKET";
+    #[cfg(target_os = "linux")]
+    VSOCK = AF_VSOCK => "AF_VSOCK";
+    #[cfg(target_os = "linux")]
+    ALG = AF_ALG => "AF_ALG";
+    #[cfg(target_os = "linux")]
+    TIPC = AF_TIPC => "AF_TIPC";
+});
 
-#[allow(non_snake_case)]
-///Socket type
-pub mod Type {
-    use super::libc::*;
-    pub const STREAM: c_int = SOCK_STREAM;
-    pub const DATAGRAM: c_int = SOCK_DGRAM;
-    pub const RAW: c_int = SOCK_RAW;
-    pub const SEQPACKET: c_int = SOCK_SEQPACKET;
+impl_constants!(SockType {
+    STREAM = SOCK_STREAM => "SOCK_STREAM";
+    DATAGRAM = SOCK_DGRAM => "SOCK_DGRAM";
+    RAW = SOCK_RAW => "SOCK_RAW";
+    SEQPACKET = SOCK_SEQPACKET => "SOCK_SEQPACKET";
+});
+
+impl SockType {
     #[cfg(not(target_os = "macos"))]
-    ///Applied through bitwise OR
-    pub const NONBLOCK: c_int = SOCK_NONBLOCK;
+    pub(crate) const FLAGS: &'static [(c_int, &'static str)] = &[(SOCK_NONBLOCK, "SOCK_NONBLOCK"), (SOCK_CLOEXEC, "SOCK_CLOEXEC")];
+    #[cfg(target_os = "macos")]
+    pub(crate) const FLAGS: &'static [(c_int, &'static str)] = &[];
+
     #[cfg(not(target_os = "macos"))]
-    ///Applied through bitwise OR
-    pub const CLOEXEC: c_int = SOCK_CLOEXEC;
+    ///Returns type with `SOCK_NONBLOCK` set.
+    pub fn nonblocking(self) -> SockType {
+        SockType(self.0 | SOCK_NONBLOCK)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    ///Returns type with `SOCK_CLOEXEC` set.
+    pub fn cloexec(self) -> SockType {
+        SockType(self.0 | SOCK_CLOEXEC)
+    }
 }
 
-#[allow(non_snake_case, non_upper_case_globals)]
-///Socket protocol
-pub mod Protocol {
+impl_constants!(Protocol {
+    NONE = 0 => "IPPROTO_IP";
+    ICMPv4 = 1 => "IPPROTO_ICMP";
+    TCP = 6 => "IPPROTO_TCP";
+    UDP = 17 => "IPPROTO_UDP";
+    ICMPv6 = 58 => "IPPROTO_ICMPV6";
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "freebsd", target_os = "netbsd"))]
+    SCTP = IPPROTO_SCTP => "IPPROTO_SCTP";
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    UDPLITE = IPPROTO_UDPLITE => "IPPROTO_UDPLITE";
+    RAW = IPPROTO_RAW => "IPPROTO_RAW";
+    #[cfg(target_os = "linux")]
+    MPTCP = IPPROTO_MPTCP => "IPPROTO_MPTCP";
+});
+
+mod flags {
+    //bitflags 0.8 expands to `try!`
+    #![allow(deprecated)]
     use super::libc::*;
-    pub const NONE: c_int = 0;
-    pub const ICMPv4: c_int = 1;
-    pub const TCP: c_int = 6;
-    pub const UDP: c_int = 17;
-    pub const ICMPv6: c_int = 58;
+
+    bitflags! (
+        ///Possible flags for `accept4()`
+        ///
+        ///Where `accept4()` is unavailable (e.g. macOS), flags are emulated after accept
+        ///and their bits carry no meaning for the OS.
+        pub flags AcceptFlags: c_int {
+            const NON_BLOCKING    = SOCK_NONBLOCK,
+            const NON_INHERITABLE = SOCK_CLOEXEC,
+        }
+    );
+
+    bitflags! (
+        ///Flags of send and receive operations.
+        ///
+        ///Some of them (`TRUNC`, `CTRUNC`, `EOR`, `OOB`) are also reported back by received-message APIs.
+        pub flags MsgFlags: c_int {
+            ///Receives data without removing it from queue.
+            const PEEK     = MSG_PEEK,
+            ///Performs operation in non-blocking mode.
+            const DONTWAIT = MSG_DONTWAIT,
+            ///Waits until whole buffer is filled.
+            const WAITALL  = MSG_WAITALL,
+            ///Returns real length of datagram even if it was longer than buffer.
+            const TRUNC    = MSG_TRUNC,
+            ///Control data was discarded due to lack of space.
+            const CTRUNC   = MSG_CTRUNC,
+            ///End of record.
+            const EOR      = MSG_EOR,
+            ///Out-of-band data.
+            const OOB      = MSG_OOB,
+            #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+            ///Doesn't raise `SIGPIPE` when peer closed connection.
+            const NOSIGNAL = MSG_NOSIGNAL,
+            #[cfg(target_os = "linux")]
+            ///More data is coming.
+            const MORE     = MSG_MORE,
+            #[cfg(target_os = "linux")]
+            ///Tells link layer that forward progress happened.
+            const CONFIRM  = MSG_CONFIRM,
+            #[cfg(target_os = "linux")]
+            ///Receives queued errors from error queue.
+            const ERRQUEUE = MSG_ERRQUEUE,
+            #[cfg(target_os = "linux")]
+            ///Sends data without copying it.
+            const ZEROCOPY = MSG_ZEROCOPY,
+            #[cfg(target_os = "linux")]
+            ///Sends data in TCP SYN.
+            const FASTOPEN = MSG_FASTOPEN,
+        }
+    );
+
+    bitflags! (
+        ///Flags of network interface.
+        pub flags InterfaceFlags: c_int {
+            ///Interface is administratively up.
+            const UP          = IFF_UP,
+            ///Broadcast address is valid.
+            const BROADCAST   = IFF_BROADCAST,
+            ///Interface is loopback.
+            const LOOPBACK    = IFF_LOOPBACK,
+            ///Interface is point-to-point link.
+            const POINTOPOINT = IFF_POINTOPOINT,
+            ///Interface is operational.
+            const RUNNING     = IFF_RUNNING,
+            ///No ARP protocol.
+            const NOARP       = IFF_NOARP,
+            ///Interface receives all packets.
+            const PROMISC     = IFF_PROMISC,
+            ///Interface supports multicast.
+            const MULTICAST   = IFF_MULTICAST,
+        }
+    );
+
+    bitflags! (
+        ///Flags of name resolution i.e. `getaddrinfo()` hints.
+        pub flags ResolveFlags: c_int {
+            ///Returns wildcard address suitable for `bind()` when host is not specified.
+            const PASSIVE     = AI_PASSIVE,
+            ///Returns addresses of family only if it is configured on local system.
+            const ADDRCONFIG  = AI_ADDRCONFIG,
+            ///Host must be numeric address, no lookup is performed.
+            const NUMERICHOST = AI_NUMERICHOST,
+        }
+    );
 }
 
-#[allow(non_snake_case)]
-///Possible flags for `accept4()`
-bitflags! (pub flags AcceptFlags: c_int {
-    const NON_BLOCKING    = SOCK_NONBLOCK,
-    const NON_INHERITABLE = SOCK_CLOEXEC,
-});
+pub use self::flags::*;
 
 #[repr(i32)]
 #[derive(Copy, Clone)]
@@ -195,6 +549,18 @@ pub enum ShutdownType {
 
 impl_into_trait!(ShutdownType);
 
+///Outcome of `Socket::recv_msg`
+pub struct RecvMsg {
+    ///Number of received bytes.
+    pub len: usize,
+    ///Address of sender, if there is any and it is of IP family.
+    pub addr: Option<net::SocketAddr>,
+    ///Number of bytes written into control buffer.
+    pub control_len: usize,
+    ///Flags set by `recvmsg()` e.g. `TRUNC`, `CTRUNC` or `EOR`.
+    pub flags: MsgFlags
+}
+
 ///Raw socket
 pub struct Socket {
     inner: SOCKET
@@ -203,16 +569,44 @@ pub struct Socket {
 impl Socket {
     ///Initializes new socket.
     ///
-    ///Corresponds to C connect()
-    pub fn new(family: c_int, _type: c_int, protocol: c_int) -> io::Result<Socket> {
-        unsafe {
-            match socket(family, _type, protocol) {
-                SOCKET_ERROR => Err(io::Error::last_os_error()),
-                fd => Ok(Socket {
+    ///Corresponds to C socket()
+    ///
+    ///Socket is not inheritable by child processes.
+    ///Where possible `SOCK_CLOEXEC` is set atomically, otherwise it is set right after creation.
+    ///Use `new_inheritable` to opt-out.
+    ///
+    ///Sending on socket never raises `SIGPIPE`, see `send`.
+    pub fn new(domain: Domain, ty: SockType, protocol: Protocol) -> Result<Socket, SocketError> {
+        #[cfg(not(target_os = "macos"))]
+        {
+            Socket::new_inheritable(domain, ty.cloexec(), protocol)
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            let socket = Socket::new_inheritable(domain, ty, protocol)?;
+            socket.set_inheritable(false)?;
+            Ok(socket)
+        }
+    }
+
+    ///Initializes new socket, passing type to socket() as it is.
+    ///
+    ///Socket is inherited by child processes unless `SockType::cloexec()` is specified.
+    pub fn new_inheritable(domain: Domain, ty: SockType, protocol: Protocol) -> Result<Socket, SocketError> {
+        let socket = unsafe {
+            match socket(domain.into(), ty.into(), protocol.into()) {
+                SOCKET_ERROR => return Err(SocketError::last_os_error(Operation::Socket)),
+                fd => Socket {
                     inner: fd
-                }),
+                },
             }
-        }
+        };
+
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        socket.set_opt(SOL_SOCKET, SO_NOSIGPIPE, 1 as c_int)?;
+
+        Ok(socket)
     }
 
     ///Returns underlying socket descriptor.
@@ -227,36 +621,53 @@ impl Socket {
     ///Wraps `getsockname()`
     ///
     ///Available for binded/connected sockets.
-    pub fn name(&self) -> io::Result<net::SocketAddr> {
+    pub fn name(&self) -> Result<net::SocketAddr, SocketError> {
         unsafe {
             let mut storage: sockaddr_storage = mem::zeroed();
             let mut len = mem::size_of_val(&storage) as socklen_t;
 
             match getsockname(self.inner, &mut storage as *mut _ as *mut _, &mut len) {
-                SOCKET_ERROR => Err(io::Error::last_os_error()),
-                _ => sockaddr_to_addr(&storage, len)
+                SOCKET_ERROR => Err(SocketError::last_os_error(Operation::GetName)),
+                _ => sockaddr_to_addr(&storage, len).map_err(|error| SocketError::new(Operation::GetName, error))
+            }
+        }
+    }
+
+    ///Retrieves address of connected peer.
+    ///
+    ///Wraps `getpeername()`
+    ///
+    ///Fails with `NotConnected` until connection is established.
+    pub fn peer_name(&self) -> Result<net::SocketAddr, SocketError> {
+        unsafe {
+            let mut storage: sockaddr_storage = mem::zeroed();
+            let mut len = mem::size_of_val(&storage) as socklen_t;
+
+            match getpeername(self.inner, &mut storage as *mut _ as *mut _, &mut len) {
+                SOCKET_ERROR => Err(SocketError::last_os_error(Operation::GetPeerName)),
+                _ => sockaddr_to_addr(&storage, len).map_err(|error| SocketError::new(Operation::GetPeerName, error))
             }
         }
     }
 
     ///Binds socket to address.
-    pub fn bind(&self, addr: &net::SocketAddr) -> io::Result<()> {
-        let (addr, len) = get_raw_addr(addr);
+    pub fn bind(&self, addr: &net::SocketAddr) -> Result<(), SocketError> {
+        let (raw_addr, len) = get_raw_addr(addr);
 
         unsafe {
-            match bind(self.inner, addr, len) {
+            match bind(self.inner, &raw_addr as *const _ as *const _, len) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Bind).with_addr(*addr))
             }
         }
     }
 
     ///Listens for incoming connections on this socket.
-    pub fn listen(&self, backlog: c_int) -> io::Result<()> {
+    pub fn listen(&self, backlog: c_int) -> Result<(), SocketError> {
         unsafe {
             match listen(self.inner, backlog) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Listen))
             }
         }
     }
@@ -264,12 +675,12 @@ impl Socket {
     ///Receives some bytes from socket
     ///
     ///Number of received bytes is returned on success
-    pub fn recv(&self, buf: &mut [u8], flags: c_int) -> io::Result<usize> {
+    pub fn recv(&self, buf: &mut [u8], flags: MsgFlags) -> Result<usize, SocketError> {
         let len = buf.len();
 
         unsafe {
-            match recv(self.inner, buf.as_mut_ptr() as *mut c_void, len, flags) {
-                -1 => Err(io::Error::last_os_error()),
+            match recv(self.inner, buf.as_mut_ptr() as *mut c_void, len, flags.bits()) {
+                -1 => Err(SocketError::last_os_error(Operation::Recv)),
                 n => Ok(n as usize)
             }
         }
@@ -278,42 +689,98 @@ impl Socket {
     ///Receives some bytes from socket
     ///
     ///Number of received bytes and remote address are returned on success.
-    pub fn recv_from(&self, buf: &mut [u8], flags: c_int) -> io::Result<(usize, net::SocketAddr)> {
+    pub fn recv_from(&self, buf: &mut [u8], flags: MsgFlags) -> Result<(usize, net::SocketAddr), SocketError> {
         let len = buf.len();
 
         unsafe {
             let mut storage: sockaddr_storage = mem::zeroed();
             let mut storage_len = mem::size_of_val(&storage) as socklen_t;
 
-            match recvfrom(self.inner, buf.as_mut_ptr() as *mut c_void, len, flags, &mut storage as *mut _ as *mut _, &mut storage_len) {
-                -1 => Err(io::Error::last_os_error()),
+            match recvfrom(self.inner, buf.as_mut_ptr() as *mut c_void, len, flags.bits(), &mut storage as *mut _ as *mut _, &mut storage_len) {
+                -1 => Err(SocketError::last_os_error(Operation::Recv)),
                 n => {
-                    let peer_addr = sockaddr_to_addr(&storage, storage_len)?;
+                    let peer_addr = sockaddr_to_addr(&storage, storage_len).map_err(|error| SocketError::new(Operation::Recv, error))?;
                     Ok((n as usize, peer_addr))
                 }
             }
         }
     }
 
+    ///Receives message into multiple buffers, along with its ancillary data.
+    ///
+    ///Wraps `recvmsg()`
+    ///
+    ///Control messages are written into `control`, which may be empty if they are of no interest.
+    ///
+    ///Descriptors received through `SCM_RIGHTS` are not inheritable, as `MSG_CMSG_CLOEXEC`
+    ///is always added to `flags` on platforms that support it.
+    pub fn recv_msg(&self, bufs: &mut [io::IoSliceMut], control: &mut [u8], flags: MsgFlags) -> Result<RecvMsg, SocketError> {
+        unsafe {
+            let mut storage: sockaddr_storage = mem::zeroed();
+            let mut msg: msghdr = mem::zeroed();
+            msg.msg_name = &mut storage as *mut _ as *mut c_void;
+            msg.msg_namelen = mem::size_of_val(&storage) as socklen_t;
+            //IoSliceMut is guaranteed to be ABI compatible with iovec
+            msg.msg_iov = bufs.as_mut_ptr() as *mut iovec;
+            msg.msg_iovlen = bufs.len() as _;
+            if !control.is_empty() {
+                msg.msg_control = control.as_mut_ptr() as *mut c_void;
+                msg.msg_controllen = control.len() as _;
+            }
+
+            match recvmsg(self.inner, &mut msg, flags.bits() | MSG_CMSG_CLOEXEC) {
+                -1 => Err(SocketError::last_os_error(Operation::Recv)),
+                n => {
+                    let addr = match msg.msg_namelen {
+                        0 => None,
+                        len => sockaddr_to_addr(&storage, len).ok()
+                    };
+
+                    Ok(RecvMsg {
+                        len: n as usize,
+                        addr,
+                        control_len: msg.msg_controllen as usize,
+                        flags: MsgFlags::from_bits_truncate(msg.msg_flags)
+                    })
+                }
+            }
+        }
+    }
+
     ///Sends some bytes through socket.
     ///
     ///Number of sent bytes is returned.
-    pub fn send(&self, buf: &[u8], flags: c_int) -> io::Result<usize> {
+    ///
+    ///`SIGPIPE` is never raised when peer closed connection,
+    ///instead error of kind `BrokenPipe` is returned.
+    ///On Linux `MSG_NOSIGNAL` is always added to `flags`, while on macOS and iOS socket is created with `SO_NOSIGPIPE`.
+    pub fn send(&self, buf: &[u8], flags: MsgFlags) -> Result<usize, SocketError> {
         let len = buf.len();
 
         unsafe {
-            match send(self.inner, buf.as_ptr() as *const c_void, len, flags) {
-                -1 => {
-                    let error = io::Error::last_os_error();
-                    let raw_code = error.raw_os_error().unwrap();
+            match send(self.inner, buf.as_ptr() as *const c_void, len, flags.bits() | MSG_NOSIGNAL) {
+                -1 => Err(SocketError::last_os_error(Operation::Send)),
+                n => Ok(n as usize)
+            }
+        }
+    }
 
-                    if raw_code == SOCKET_SHUTDOWN {
-                        Ok(0)
-                    }
-                    else {
-                        Err(error)
-                    }
-                },
+    ///Sends bytes from multiple buffers through socket.
+    ///
+    ///Wraps `sendmsg()`
+    ///
+    ///Number of sent bytes is returned.
+    ///
+    ///Never raises `SIGPIPE`, see `send`.
+    pub fn send_vectored(&self, bufs: &[io::IoSlice], flags: MsgFlags) -> Result<usize, SocketError> {
+        unsafe {
+            let mut msg: msghdr = mem::zeroed();
+            //IoSlice is guaranteed to be ABI compatible with iovec
+            msg.msg_iov = bufs.as_ptr() as *mut iovec;
+            msg.msg_iovlen = bufs.len() as _;
+
+            match sendmsg(self.inner, &msg, flags.bits() | MSG_NOSIGNAL) {
+                -1 => Err(SocketError::last_os_error(Operation::Send)),
                 n => Ok(n as usize)
             }
         }
@@ -325,23 +792,15 @@ impl Socket {
     ///
     ///Note: the socket will be bound, if it isn't already.
     ///Use method `name` to determine address.
-    pub fn send_to(&self, buf: &[u8], peer_addr: &net::SocketAddr, flags: c_int) -> io::Result<usize> {
+    ///
+    ///Never raises `SIGPIPE`, see `send`.
+    pub fn send_to(&self, buf: &[u8], peer_addr: &net::SocketAddr, flags: MsgFlags) -> Result<usize, SocketError> {
         let len = buf.len();
         let (addr, addr_len) = get_raw_addr(peer_addr);
 
         unsafe {
-            match sendto(self.inner, buf.as_ptr() as *const c_void, len, flags, addr, addr_len) {
-                -1 => {
-                    let error = io::Error::last_os_error();
-                    let raw_code = error.raw_os_error().unwrap();
-
-                    if raw_code == SOCKET_SHUTDOWN {
-                        Ok(0)
-                    }
-                    else {
-                        Err(error)
-                    }
-                },
+            match sendto(self.inner, buf.as_ptr() as *const c_void, len, flags.bits() | MSG_NOSIGNAL, &addr as *const _ as *const _, addr_len) {
+                -1 => Err(SocketError::last_os_error(Operation::Send).with_addr(*peer_addr)),
                 n => Ok(n as usize)
             }
         }
@@ -357,77 +816,170 @@ impl Socket {
     ///
     ///Depending on the operating system's availability of the `accept4(2)` system call this call
     ///either pass the flags on to the operating system or emulate the call using `accept(2)`.
-    pub fn accept4(&self, flags: AcceptFlags) -> io::Result<(Socket, net::SocketAddr)> {
-        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "dragonflybsd"))]
+    pub fn accept4(&self, flags: AcceptFlags) -> Result<(Socket, net::SocketAddr), SocketError> {
+        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "dragonfly"))]
         unsafe {
             let mut storage: sockaddr_storage = mem::zeroed();
             let mut len = mem::size_of_val(&storage) as socklen_t;
 
             match accept4(self.inner, &mut storage as *mut _ as *mut _, &mut len, flags.bits()) {
-                SOCKET_ERROR => Err(io::Error::last_os_error()),
-                sock @ _ => {
-                    let addr = sockaddr_to_addr(&storage, len)?;
-                    Ok((Socket { inner: sock, }, addr))
+                SOCKET_ERROR => Err(SocketError::last_os_error(Operation::Accept)),
+                sock => {
+                    let sock = Socket { inner: sock };
+                    let addr = sockaddr_to_addr(&storage, len).map_err(|error| SocketError::new(Operation::Accept, error))?;
+                    Ok((sock, addr))
                 }
             }
         }
 
-        #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "dragonflybsd")))]
+        #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "dragonfly")))]
         {
-            self.accept().map(|(sock, addr)| {
-                // Emulate the two most common (and useful) `accept4` flags using `ioctl`/`fcntl`
-                //
-                // The only errors that can happen here fall into two categories:
-                //
-                //  * Programming errors on our side
-                //    (unlikely, but in this case panicking is actually the right thing to do anyway)
-                //  * Another thread causing havoc with random file descriptors
-                //    (always very bad and nothing, since there is absolutely nothing
-                //     that we OR USER can do about this)
-                sock.set_blocking(!flags.contains(NON_BLOCKING)).expect("Setting newly obtained client socket blocking mode");
-                sock.set_inheritable(!flags.contains(NON_INHERITABLE)).expect("Setting newly obtained client socket inheritance mode");
+            let (sock, addr) = self.accept_raw()?;
 
-                (sock, addr)
-            })
+            // Emulate the two most common (and useful) `accept4` flags using `ioctl`/`fcntl`
+            //
+            // On failure accepted socket is dropped, closing its descriptor.
+            sock.set_blocking(!flags.contains(NON_BLOCKING))?;
+            sock.set_inheritable(!flags.contains(NON_INHERITABLE))?;
+
+            Ok((sock, addr))
         }
     }
 
 
     ///Accept a new incoming client connection and return its files descriptor and address.
     ///
-    ///As this uses the classic `accept(2)` system call internally, you are **strongly advised** to
-    ///use the `.accept4()` method instead to get defined blocking and inheritance semantics for
-    ///the created file descriptor.
-    pub fn accept(&self) -> io::Result<(Socket, net::SocketAddr)> {
+    ///Accepted socket is not inheritable by child processes, which is the same as
+    ///`.accept4(NON_INHERITABLE)`. Use `.accept4(AcceptFlags::empty())` to opt-out.
+    pub fn accept(&self) -> Result<(Socket, net::SocketAddr), SocketError> {
+        #[cfg(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "dragonfly"))]
+        {
+            self.accept4(NON_INHERITABLE)
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "dragonfly")))]
+        {
+            let (sock, addr) = self.accept_raw()?;
+            sock.set_inheritable(false)?;
+            Ok((sock, addr))
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "freebsd", target_os = "openbsd", target_os = "dragonfly")))]
+    fn accept_raw(&self) -> Result<(Socket, net::SocketAddr), SocketError> {
         unsafe {
             let mut storage: sockaddr_storage = mem::zeroed();
             let mut len = mem::size_of_val(&storage) as socklen_t;
 
             match accept(self.inner, &mut storage as *mut _ as *mut _, &mut len) {
-                SOCKET_ERROR => Err(io::Error::last_os_error()),
-                sock @ _ => {
-                    let addr = sockaddr_to_addr(&storage, len)?;
-                    Ok((Socket { inner: sock }, addr))
+                SOCKET_ERROR => Err(SocketError::last_os_error(Operation::Accept)),
+                sock => {
+                    let sock = Socket { inner: sock };
+                    #[cfg(any(target_os = "macos", target_os = "ios"))]
+                    sock.set_opt(SOL_SOCKET, SO_NOSIGPIPE, 1 as c_int)?;
+                    let addr = sockaddr_to_addr(&storage, len).map_err(|error| SocketError::new(Operation::Accept, error))?;
+                    Ok((sock, addr))
                 }
             }
         }
     }
 
 
+    ///Returns iterator over incoming connections, accepted with `flags`.
+    ///
+    ///See `Incoming` for details.
+    pub fn incoming(&self, flags: AcceptFlags) -> Incoming<'_> {
+        Incoming::new(self, flags)
+    }
+
     ///Connects socket with remote address.
-    pub fn connect(&self, addr: &net::SocketAddr) -> io::Result<()> {
-        let (addr, len) = get_raw_addr(addr);
+    pub fn connect(&self, addr: &net::SocketAddr) -> Result<(), SocketError> {
+        let (raw_addr, len) = get_raw_addr(addr);
 
         unsafe {
-            match connect(self.inner, addr, len) {
+            match connect(self.inner, &raw_addr as *const _ as *const _, len) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Connect).with_addr(*addr))
             }
         }
     }
 
+    ///Connects socket with remote address, giving up once timeout expires.
+    ///
+    ///Socket is switched into non-blocking mode for the duration of connect and its
+    ///original mode is restored afterwards.
+    ///
+    ///Returns error of kind `TimedOut` if connection is not established in time.
+    pub fn connect_timeout(&self, addr: &net::SocketAddr, timeout: time::Duration) -> Result<(), SocketError> {
+        if timeout == time::Duration::from_secs(0) {
+            return Err(SocketError::custom(Operation::Connect, io::ErrorKind::InvalidInput, "cannot set a 0 duration timeout").with_addr(*addr));
+        }
+
+        let is_blocking = self.is_blocking()?;
+
+        if is_blocking {
+            self.set_blocking(false)?;
+        }
+
+        let result = self.connect_wait(addr, timeout);
+
+        if is_blocking {
+            //Failure to restore mode must not hide outcome of connect.
+            let restored = self.set_blocking(true);
+            if result.is_ok() {
+                restored?;
+            }
+        }
+
+        result
+    }
+
+    fn connect_wait(&self, addr: &net::SocketAddr, timeout: time::Duration) -> Result<(), SocketError> {
+        match self.connect(addr) {
+            Ok(()) => return Ok(()),
+            Err(ref error) if error.raw_os_error() == Some(EINPROGRESS) => (),
+            Err(error) => return Err(error)
+        }
+
+        let deadline = time::Instant::now() + timeout;
+        let mut pollfd = pollfd { fd: self.inner, events: POLLOUT, revents: 0 };
+
+        loop {
+            let now = time::Instant::now();
+            if now >= deadline {
+                return Err(SocketError::custom(Operation::Connect, io::ErrorKind::TimedOut, "connection timed out").with_addr(*addr));
+            }
+
+            match unsafe { poll(&mut pollfd, 1 as nfds_t, duration_to_poll_ms(deadline - now)) } {
+                -1 => {
+                    let error = SocketError::last_os_error(Operation::Select);
+                    if error.kind() != io::ErrorKind::Interrupted {
+                        return Err(error);
+                    }
+                },
+                0 => (),
+                _ => break
+            }
+        }
+
+        match self.take_error()? {
+            Some(error) => Err(error.with_addr(*addr)),
+            None => Ok(())
+        }
+    }
+
+    ///Retrieves and clears pending error of socket i.e. `SO_ERROR`.
+    ///
+    ///Outcome of non-blocking connect is reported this way, therefore error is attributed to `Operation::Connect`.
+    pub fn take_error(&self) -> Result<Option<SocketError>, SocketError> {
+        match self.get_opt::<c_int>(SOL_SOCKET, SO_ERROR)? {
+            0 => Ok(None),
+            code => Ok(Some(SocketError::from_raw_os_error(Operation::Connect, code)))
+        }
+    }
+
     ///Retrieves socket option.
-    pub fn get_opt<T>(&self, level: c_int, name: c_int) -> io::Result<T> {
+    pub fn get_opt<T>(&self, level: c_int, name: c_int) -> Result<T, SocketError> {
         unsafe {
             let mut value: T = mem::zeroed();
             let value_ptr = &mut value as *mut T as *mut c_void;
@@ -435,7 +987,7 @@ impl Socket {
 
             match getsockopt(self.inner, level, name, value_ptr, &mut value_len) {
                 0 => Ok(value),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::GetOpt { level, name }))
             }
         }
     }
@@ -443,35 +995,155 @@ impl Socket {
     ///Sets socket option
     ///
     ///Value is generally integer or C struct.
-    pub fn set_opt<T>(&self, level: c_int, name: c_int, value: T) -> io::Result<()> {
+    pub fn set_opt<T>(&self, level: c_int, name: c_int, value: T) -> Result<(), SocketError> {
         unsafe {
             let value = &value as *const T as *const c_void;
 
             match setsockopt(self.inner, level, name, value, mem::size_of::<T>() as socklen_t) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::SetOpt { level, name }))
             }
         }
     }
 
     ///Sets I/O parameters of socket.
-    pub fn ioctl(&self, request: IoctlRequestT, value: c_ulong) -> io::Result<()> {
+    ///
+    ///Anything written back by system is discarded, use `ioctl_mut` or `ioctl_get` to retrieve it.
+    pub fn ioctl(&self, request: IoctlRequestT, value: c_ulong) -> Result<(), SocketError> {
         unsafe {
             let mut value = value;
             let value = &mut value as *mut c_ulong;
 
             match ioctl(self.inner, request, value) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Ioctl))
             }
         }
     }
 
+    ///Performs `ioctl()` with pointer to `value`, which system reads and/or writes back.
+    ///
+    ///Returns non-negative result of `ioctl()`.
+    ///
+    ///# Safety
+    ///
+    ///`T` must be the type `request` expects, as system accesses it through pointer without knowing its size.
+    pub unsafe fn ioctl_mut<T>(&self, request: IoctlRequestT, value: &mut T) -> Result<c_int, SocketError> {
+        match ioctl(self.inner, request, value as *mut T) {
+            -1 => Err(SocketError::last_os_error(Operation::Ioctl)),
+            result => Ok(result)
+        }
+    }
+
+    ///Retrieves value of `ioctl()`, which writes its result into `T`.
+    ///
+    ///# Safety
+    ///
+    ///Same as for `ioctl_mut`.
+    pub unsafe fn ioctl_get<T>(&self, request: IoctlRequestT) -> Result<T, SocketError> {
+        let mut value: T = mem::zeroed();
+        self.ioctl_mut(request, &mut value)?;
+        Ok(value)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ///Performs interface `request` on interface with `name`.
+    fn interface_request(&self, request: c_ulong, name: &str) -> Result<ifreq, SocketError> {
+        let mut req: ifreq = unsafe { mem::zeroed() };
+
+        if name.is_empty() || name.len() >= req.ifr_name.len() || name.as_bytes().contains(&0) {
+            return Err(SocketError::custom(Operation::Ioctl, io::ErrorKind::InvalidInput, "invalid interface name"));
+        }
+
+        for (dst, &byte) in req.ifr_name.iter_mut().zip(name.as_bytes()) {
+            *dst = byte as c_char;
+        }
+
+        unsafe {
+            self.ioctl_mut(request as IoctlRequestT, &mut req)?;
+        }
+
+        Ok(req)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ///Returns index of network interface with `name` i.e. `SIOCGIFINDEX`.
+    pub fn interface_index(&self, name: &str) -> Result<u32, SocketError> {
+        let req = self.interface_request(SIOCGIFINDEX, name)?;
+        Ok(unsafe { req.ifr_ifru.ifru_ifindex } as u32)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ///Returns MTU of network interface with `name` i.e. `SIOCGIFMTU`.
+    pub fn interface_mtu(&self, name: &str) -> Result<u32, SocketError> {
+        let req = self.interface_request(SIOCGIFMTU, name)?;
+        Ok(unsafe { req.ifr_ifru.ifru_mtu } as u32)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ///Returns hardware (MAC) address of network interface with `name` i.e. `SIOCGIFHWADDR`.
+    pub fn interface_hwaddr(&self, name: &str) -> Result<[u8; 6], SocketError> {
+        let req = self.interface_request(SIOCGIFHWADDR, name)?;
+        let data = unsafe { req.ifr_ifru.ifru_hwaddr.sa_data };
+
+        let mut result = [0; 6];
+        for (dst, &byte) in result.iter_mut().zip(data.iter()) {
+            *dst = byte as u8;
+        }
+
+        Ok(result)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    ///Returns flags of network interface with `name` i.e. `SIOCGIFFLAGS`.
+    pub fn interface_flags(&self, name: &str) -> Result<InterfaceFlags, SocketError> {
+        let req = self.interface_request(SIOCGIFFLAGS, name)?;
+        //Flags are `short`, which must not be sign extended.
+        Ok(InterfaceFlags::from_bits_truncate(unsafe { req.ifr_ifru.ifru_flags } as u16 as c_int))
+    }
+
     ///Sets non-blocking mode.
-    pub fn set_blocking(&self, value: bool) -> io::Result<()> {
+    pub fn set_blocking(&self, value: bool) -> Result<(), SocketError> {
         self.ioctl(FIONBIO, (!value) as c_ulong)
     }
 
+    ///Returns whether socket is in blocking mode i.e. `O_NONBLOCK` is not set.
+    pub(crate) fn is_blocking(&self) -> Result<bool, SocketError> {
+        match unsafe { fcntl(self.inner, F_GETFL) } {
+            -1 => Err(SocketError::last_os_error(Operation::Fcntl)),
+            flags => Ok(flags & O_NONBLOCK == 0)
+        }
+    }
+
+    ///Returns number of bytes that can be received without blocking i.e. `FIONREAD`.
+    ///
+    ///For datagram sockets it is size of next datagram on Linux, but total size of queued datagrams on BSDs.
+    pub fn bytes_available(&self) -> Result<usize, SocketError> {
+        unsafe { self.ioctl_get::<c_int>(FIONREAD) }.map(|len| len as usize)
+    }
+
+    ///Returns number of bytes in send queue, which are not yet sent or acknowledged by peer.
+    ///
+    ///Uses `SIOCOUTQ` on Linux and `SO_NWRITE` on macOS.
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios"))]
+    pub fn send_queue_len(&self) -> Result<usize, SocketError> {
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let len = unsafe { self.ioctl_get::<c_int>(TIOCOUTQ as IoctlRequestT) };
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        let len = self.get_opt::<c_int>(SOL_SOCKET, SO_NWRITE);
+
+        len.map(|len| len as usize)
+    }
+
+    ///Returns whether socket is at out-of-band mark i.e. `SIOCATMARK`.
+    ///
+    ///Once at mark, all data sent before urgent byte is received and it can be retrieved with `recv_oob`.
+    pub fn at_mark(&self) -> Result<bool, SocketError> {
+        match unsafe { sockatmark(self.inner) } {
+            -1 => Err(SocketError::last_os_error(Operation::Ioctl)),
+            mark => Ok(mark == 1)
+        }
+    }
 
     ///Sets whether this socket will be inherited by newly created processes or not.
     ///
@@ -484,7 +1156,7 @@ impl Socket {
     ///(or similar) should therefore not expect this flag to have any effect on spawned off workers;
     ///you're advised to manually call `.close()` on the socket instance in the worker process
     ///instead. The standard library's `std::process` facility is not impacted by this however.
-    pub fn set_inheritable(&self, value: bool) -> io::Result<()> {
+    pub fn set_inheritable(&self, value: bool) -> Result<(), SocketError> {
         // Some (or possibly all?) OS's support the `FIOCLEX` and `FIONCLEX`
         // `ioctl`s instead, however there is no support for that in `libc`
         // currently and no usable documentation for figuring out who supports
@@ -492,17 +1164,17 @@ impl Socket {
         unsafe {
             let mut flags: libc::c_int = libc::fcntl(self.inner, libc::F_GETFD);
             if flags < 0 {
-                return Err(io::Error::last_os_error());
+                return Err(SocketError::last_os_error(Operation::Fcntl));
             }
 
-            if value == true {
+            if value {
                 flags &= !libc::FD_CLOEXEC;
             } else {
                 flags |= libc::FD_CLOEXEC;
             }
 
             if libc::fcntl(self.inner, libc::F_SETFD, flags) < 0 {
-                return Err(io::Error::last_os_error());
+                return Err(SocketError::last_os_error(Operation::Fcntl));
             }
         }
 
@@ -512,11 +1184,11 @@ impl Socket {
     ///Returns whether this will be inherited by newly created processes or not.
     ///
     ///See `set_inheritable` for a detailed description of what this means.
-    pub fn get_inheritable(&self) -> io::Result<bool> {
+    pub fn get_inheritable(&self) -> Result<bool, SocketError> {
         unsafe {
             let flags = libc::fcntl(self.inner, libc::F_GETFD);
             if flags < 0 {
-                return Err(io::Error::last_os_error());
+                return Err(SocketError::last_os_error(Operation::Fcntl));
             }
 
             Ok((flags & libc::FD_CLOEXEC) == 0)
@@ -524,45 +1196,215 @@ impl Socket {
     }
 
 
+    ///Retrieves address family of socket.
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    fn domain(&self) -> Result<c_int, SocketError> {
+        self.get_opt::<c_int>(SOL_SOCKET, SO_DOMAIN)
+    }
+
+    ///Retrieves address family of socket.
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+    fn domain(&self) -> Result<c_int, SocketError> {
+        unsafe {
+            let mut storage: sockaddr_storage = mem::zeroed();
+            let mut len = mem::size_of_val(&storage) as socklen_t;
+
+            match getsockname(self.inner, &mut storage as *mut _ as *mut _, &mut len) {
+                SOCKET_ERROR => Err(SocketError::last_os_error(Operation::GetName)),
+                _ => Ok(storage.ss_family as c_int)
+            }
+        }
+    }
+
+    ///Verifies that socket is of type `ty` and one of `families`.
+    fn check_kind(&self, ty: c_int, families: &[c_int], listening: bool) -> Result<(), SocketError> {
+        if self.get_opt::<c_int>(SOL_SOCKET, SO_TYPE)? != ty {
+            return Err(SocketError::custom(Operation::GetOpt { level: SOL_SOCKET, name: SO_TYPE }, io::ErrorKind::InvalidInput, "socket type mismatch"));
+        }
+
+        if (self.get_opt::<c_int>(SOL_SOCKET, SO_ACCEPTCONN)? != 0) != listening {
+            return Err(SocketError::custom(Operation::GetOpt { level: SOL_SOCKET, name: SO_ACCEPTCONN }, io::ErrorKind::InvalidInput, "socket listening state mismatch"));
+        }
+
+        if !families.contains(&self.domain()?) {
+            return Err(SocketError::custom(Operation::GetName, io::ErrorKind::InvalidInput, "socket family mismatch"));
+        }
+
+        Ok(())
+    }
+
+    ///Creates new handle for the same underlying socket.
+    ///
+    ///Returned socket is not inheritable, regardless of whether original one is.
+    pub fn try_clone(&self) -> Result<Socket, SocketError> {
+        unsafe {
+            match fcntl(self.inner, F_DUPFD_CLOEXEC, 0) {
+                SOCKET_ERROR => Err(SocketError::last_os_error(Operation::Dup)),
+                fd => Ok(Socket { inner: fd })
+            }
+        }
+    }
+
+    ///Duplicates socket onto descriptor `target` using `dup2()`.
+    ///
+    ///Returned socket is inheritable, which makes it suitable for placing socket
+    ///at fixed descriptor number before `execve(2)`.
+    ///
+    ///# Safety
+    ///
+    ///If `target` is open, it is silently closed.
+    ///Caller must ensure nothing else owns `target` descriptor.
+    pub unsafe fn dup_to(&self, target: SOCKET) -> Result<Socket, SocketError> {
+        if target == self.inner {
+            return Err(SocketError::custom(Operation::Dup, io::ErrorKind::InvalidInput, "cannot duplicate socket onto itself"));
+        }
+
+        match dup2(self.inner, target) {
+            SOCKET_ERROR => Err(SocketError::last_os_error(Operation::Dup)),
+            fd => Ok(Socket { inner: fd })
+        }
+    }
+
+    ///Sets `SO_REUSEADDR` option.
+    pub fn set_reuse_address(&self, value: bool) -> Result<(), SocketError> {
+        self.set_opt(SOL_SOCKET, SO_REUSEADDR, value as c_int)
+    }
+
+    ///Sets `SO_REUSEPORT` option.
+    pub fn set_reuse_port(&self, value: bool) -> Result<(), SocketError> {
+        self.set_opt(SOL_SOCKET, SO_REUSEPORT, value as c_int)
+    }
+
+    ///Sets `TCP_NODELAY` option.
+    pub fn set_nodelay(&self, value: bool) -> Result<(), SocketError> {
+        self.set_opt(IPPROTO_TCP, TCP_NODELAY, value as c_int)
+    }
+
+    ///Sets size of receive buffer i.e. `SO_RCVBUF` option.
+    pub fn set_recv_buffer_size(&self, size: usize) -> Result<(), SocketError> {
+        self.set_opt(SOL_SOCKET, SO_RCVBUF, cmp::min(size, c_int::MAX as usize) as c_int)
+    }
+
+    ///Sets size of send buffer i.e. `SO_SNDBUF` option.
+    pub fn set_send_buffer_size(&self, size: usize) -> Result<(), SocketError> {
+        self.set_opt(SOL_SOCKET, SO_SNDBUF, cmp::min(size, c_int::MAX as usize) as c_int)
+    }
+
+    ///Sets `IPV6_V6ONLY` option.
+    ///
+    ///Note: it must be set before socket is bound.
+    pub fn set_only_v6(&self, value: bool) -> Result<(), SocketError> {
+        self.set_opt(IPPROTO_IPV6, IPV6_V6ONLY, value as c_int)
+    }
+
+    #[cfg(target_os = "linux")]
+    ///Binds socket to network interface with name `device` i.e. `SO_BINDTODEVICE` option.
+    ///
+    ///Empty name removes binding.
+    pub fn bind_device(&self, device: &str) -> Result<(), SocketError> {
+        unsafe {
+            match setsockopt(self.inner, SOL_SOCKET, SO_BINDTODEVICE, device.as_ptr() as *const c_void, device.len() as socklen_t) {
+                0 => Ok(()),
+                _ => Err(SocketError::last_os_error(Operation::SetOpt { level: SOL_SOCKET, name: SO_BINDTODEVICE }))
+            }
+        }
+    }
+
+    ///Sets timeout of receive operations i.e. `SO_RCVTIMEO` option.
+    ///
+    ///`None` means no timeout.
+    pub fn set_read_timeout(&self, timeout: Option<time::Duration>) -> Result<(), SocketError> {
+        self.set_timeout(SO_RCVTIMEO, timeout)
+    }
+
+    ///Sets timeout of send operations i.e. `SO_SNDTIMEO` option.
+    ///
+    ///`None` means no timeout.
+    pub fn set_write_timeout(&self, timeout: Option<time::Duration>) -> Result<(), SocketError> {
+        self.set_timeout(SO_SNDTIMEO, timeout)
+    }
+
+    fn set_timeout(&self, name: c_int, timeout: Option<time::Duration>) -> Result<(), SocketError> {
+        let timeout = duration_to_timeval(timeout).map_err(|error| SocketError::new(Operation::SetOpt { level: SOL_SOCKET, name }, error))?;
+        self.set_opt(SOL_SOCKET, name, timeout)
+    }
+
     ///Stops receive and/or send over socket.
-    pub fn shutdown(&self, direction: ShutdownType) -> io::Result<()> {
+    pub fn shutdown(&self, direction: ShutdownType) -> Result<(), SocketError> {
         unsafe {
             match shutdown(self.inner, direction.into()) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Shutdown))
             }
         }
     }
 
-    ///Closes socket.
+    ///Closes socket, reporting error of `close()`.
+    ///
+    ///Note: on `Drop` socket will be closed on its own, but any error is ignored.
     ///
-    ///Note: on `Drop` socket will be closed on its own.
-    ///There is no need to close it explicitly.
-    pub fn close(&self) -> io::Result<()> {
+    ///Descriptor is released even if error is returned, including `EINTR`,
+    ///so close is never retried as the same number might already belong to another file.
+    pub fn close(self) -> Result<(), SocketError> {
+        let fd = self.into_raw_fd();
+
         unsafe {
-            match close(self.inner) {
+            match close(fd) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Close))
             }
         }
     }
 }
 
-fn get_raw_addr(addr: &net::SocketAddr) -> (*const sockaddr, socklen_t) {
-    match *addr {
+///Converts address into C `sockaddr_storage` and its length.
+pub(crate) fn get_raw_addr(addr: &net::SocketAddr) -> (sockaddr_storage, socklen_t) {
+    //std's address types are not guaranteed to share layout with C structs
+    //so we need to fill `sockaddr_*` ourselves.
+    let mut storage: sockaddr_storage = unsafe { mem::zeroed() };
+
+    let len = match *addr {
         net::SocketAddr::V4(ref a) => {
-            (a as *const _ as *const _, mem::size_of_val(a) as socklen_t)
+            let raw = unsafe { &mut *(&mut storage as *mut _ as *mut sockaddr_in) };
+            raw.sin_family = AF_INET as sa_family_t;
+            raw.sin_port = a.port().to_be();
+            raw.sin_addr.s_addr = u32::from_ne_bytes(a.ip().octets());
+            #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd", target_os = "dragonfly"))]
+            {
+                raw.sin_len = mem::size_of::<sockaddr_in>() as u8;
+            }
+
+            mem::size_of::<sockaddr_in>()
         }
         net::SocketAddr::V6(ref a) => {
-            (a as *const _ as *const _, mem::size_of_val(a) as socklen_t)
+            let raw = unsafe { &mut *(&mut storage as *mut _ as *mut sockaddr_in6) };
+            raw.sin6_family = AF_INET6 as sa_family_t;
+            raw.sin6_port = a.port().to_be();
+            raw.sin6_flowinfo = a.flowinfo();
+            raw.sin6_addr.s6_addr = a.ip().octets();
+            raw.sin6_scope_id = a.scope_id();
+            #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "openbsd", target_os = "netbsd", target_os = "dragonfly"))]
+            {
+                raw.sin6_len = mem::size_of::<sockaddr_in6>() as u8;
+            }
+
+            mem::size_of::<sockaddr_in6>()
         }
-    }
+    };
+
+    (storage, len as socklen_t)
 }
 
-fn sockaddr_to_addr(storage: &sockaddr_storage, len: socklen_t) -> io::Result<net::SocketAddr> {
+///Converts C `sockaddr_storage` of length `len` into address.
+///
+///Fails with `InvalidInput` if address is not of IP family or `len` is too short for it,
+///which is the case for unnamed Unix peers or when system reports no address at all.
+pub(crate) fn sockaddr_to_addr(storage: &sockaddr_storage, len: socklen_t) -> io::Result<net::SocketAddr> {
     match storage.ss_family as c_int {
         AF_INET => {
-            assert!(len as usize >= mem::size_of::<sockaddr_in>());
+            if (len as usize) < mem::size_of::<sockaddr_in>() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid addr length."));
+            }
             let storage = unsafe { *(storage as *const _ as *const sockaddr_in) };
             let address = unsafe { *(&storage.sin_addr.s_addr as *const _ as *const [u8; 4]) };
             let ip = net::Ipv4Addr::from(address);
@@ -572,9 +1414,11 @@ fn sockaddr_to_addr(storage: &sockaddr_storage, len: socklen_t) -> io::Result<ne
             Ok(net::SocketAddr::V4(net::SocketAddrV4::new(ip, storage.sin_port.to_be())))
         }
         AF_INET6 => {
-            assert!(len as usize >= mem::size_of::<sockaddr_in6>());
+            if (len as usize) < mem::size_of::<sockaddr_in6>() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid addr length."));
+            }
             let storage = unsafe { *(storage as *const _ as *const sockaddr_in6) };
-            let ip = net::Ipv6Addr::from(storage.sin6_addr.s6_addr.clone());
+            let ip = net::Ipv6Addr::from(storage.sin6_addr.s6_addr);
 
             Ok(net::SocketAddr::V6(net::SocketAddrV6::new(ip, storage.sin6_port.to_be(), storage.sin6_flowinfo, storage.sin6_scope_id)))
         }
@@ -584,10 +1428,32 @@ fn sockaddr_to_addr(storage: &sockaddr_storage, len: socklen_t) -> io::Result<ne
     }
 }
 
+#[doc(hidden)]
+///Address conversions exposed for fuzz targets and tests, not part of public API.
+pub mod fuzzing {
+    use std::io;
+    use std::net;
+
+    use super::{sockaddr_storage, socklen_t};
+
+    pub fn get_raw_addr(addr: &net::SocketAddr) -> (sockaddr_storage, socklen_t) {
+        super::get_raw_addr(addr)
+    }
+
+    pub fn sockaddr_to_addr(storage: &sockaddr_storage, len: socklen_t) -> io::Result<net::SocketAddr> {
+        super::sockaddr_to_addr(storage, len)
+    }
+}
+
 impl Drop for Socket {
+    ///Closes socket.
+    ///
+    ///Socket is not shut down, so connection stays alive for other holders of it.
+    ///Use `ShutdownOnDrop` if that is desired.
     fn drop(&mut self) {
-        let _ = self.shutdown(ShutdownType::Both);
-        let _ = self.close();
+        unsafe {
+            close(self.inner);
+        }
     }
 }
 
@@ -595,6 +1461,9 @@ use std::os::unix::io::{
     AsRawFd,
     FromRawFd,
     IntoRawFd,
+    AsFd,
+    BorrowedFd,
+    OwnedFd
 };
 
 impl AsRawFd for Socket {
@@ -617,7 +1486,103 @@ impl IntoRawFd for Socket {
     }
 }
 
+impl AsFd for Socket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        unsafe { BorrowedFd::borrow_raw(self.inner) }
+    }
+}
+
+impl From<OwnedFd> for Socket {
+    fn from(fd: OwnedFd) -> Socket {
+        Socket { inner: fd.into_raw_fd() }
+    }
+}
+
+impl From<Socket> for OwnedFd {
+    fn from(socket: Socket) -> OwnedFd {
+        unsafe { OwnedFd::from_raw_fd(socket.into_raw_fd()) }
+    }
+}
+
+macro_rules! impl_std_conversions {
+    ($($t:ty => $ty:expr, $listening:expr, [$($family:expr),+];)+) => {
+        $(
+            impl From<$t> for Socket {
+                fn from(socket: $t) -> Socket {
+                    Socket { inner: socket.into_raw_fd() }
+                }
+            }
+
+            ///Fails with `InvalidInput` if socket's type, family or listening state doesn't match.
+            impl TryFrom<Socket> for $t {
+                type Error = SocketError;
+
+                fn try_from(socket: Socket) -> Result<$t, SocketError> {
+                    socket.check_kind($ty, &[$($family),+], $listening)?;
+                    Ok(unsafe { <$t>::from_raw_fd(socket.into_raw_fd()) })
+                }
+            }
+        )+
+    };
+}
+
+impl_std_conversions!(
+    net::TcpStream => SOCK_STREAM, false, [AF_INET, AF_INET6];
+    net::TcpListener => SOCK_STREAM, true, [AF_INET, AF_INET6];
+    net::UdpSocket => SOCK_DGRAM, false, [AF_INET, AF_INET6];
+    unix_net::UnixStream => SOCK_STREAM, false, [AF_UNIX];
+    unix_net::UnixListener => SOCK_STREAM, true, [AF_UNIX];
+    unix_net::UnixDatagram => SOCK_DGRAM, false, [AF_UNIX];
+);
+
+#[cfg(feature = "mio")]
+///Allows to register socket within `mio::Poll`.
+///
+///Note: socket should be put into non-blocking mode beforehand.
+impl ::mio::event::Source for Socket {
+    fn register(&mut self, registry: &::mio::Registry, token: ::mio::Token, interests: ::mio::Interest) -> io::Result<()> {
+        ::mio::unix::SourceFd(&self.inner).register(registry, token, interests)
+    }
+
+    fn reregister(&mut self, registry: &::mio::Registry, token: ::mio::Token, interests: ::mio::Interest) -> io::Result<()> {
+        ::mio::unix::SourceFd(&self.inner).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &::mio::Registry) -> io::Result<()> {
+        ::mio::unix::SourceFd(&self.inner).deregister(registry)
+    }
+}
+
 #[inline]
+fn duration_to_timeval(timeout: Option<time::Duration>) -> io::Result<timeval> {
+    match timeout {
+        None => Ok(timeval { tv_sec: 0, tv_usec: 0 }),
+        Some(timeout) if timeout == time::Duration::from_secs(0) => {
+            Err(io::Error::new(io::ErrorKind::InvalidInput, "cannot set a 0 duration timeout"))
+        },
+        Some(timeout) => {
+            let mut result = timeval {
+                tv_sec: cmp::min(timeout.as_secs(), time_t::MAX as u64) as time_t,
+                tv_usec: timeout.subsec_micros() as suseconds_t
+            };
+
+            //Sub-microsecond timeout would otherwise turn into no timeout at all.
+            if result.tv_sec == 0 && result.tv_usec == 0 {
+                result.tv_usec = 1;
+            }
+
+            Ok(result)
+        }
+    }
+}
+
+///Converts timeout into milliseconds of `poll()`.
+pub(crate) fn duration_to_poll_ms(timeout: time::Duration) -> c_int {
+    //Round up so that we do not spin with zero timeout on sub-millisecond remainder.
+    let timeout_ms = timeout.as_secs().saturating_mul(1_000) + (timeout.subsec_nanos() as u64).div_ceil(1_000_000);
+    cmp::min(timeout_ms, c_int::MAX as u64) as c_int
+}
+
 fn ms_to_timeval(timeout_ms: u64) -> timeval {
     timeval {
         tv_sec: timeout_ms as time_t / 1000,
@@ -625,18 +1590,24 @@ fn ms_to_timeval(timeout_ms: u64) -> timeval {
     }
 }
 
-fn sockets_to_fd_set(sockets: &[&Socket]) -> (c_int, fd_set) {
+fn sockets_to_fd_set(sockets: &[&dyn AsFd]) -> Result<(c_int, fd_set), SocketError> {
     let mut max_fd: c_int = 0;
     let mut raw_fds: fd_set = unsafe { mem::zeroed() };
 
     for socket in sockets {
-        max_fd = cmp::max(max_fd, socket.inner);
+        let fd = socket.as_fd().as_raw_fd();
+        //`fd_set` is a fixed size bitmap, therefore bigger descriptors would be written out of its bounds.
+        if fd < 0 || fd as usize >= FD_SETSIZE {
+            return Err(SocketError::custom(Operation::Select, io::ErrorKind::InvalidInput, "descriptor exceeds FD_SETSIZE"));
+        }
+
+        max_fd = cmp::max(max_fd, fd);
         unsafe {
-            FD_SET(socket.inner, &mut raw_fds);
+            FD_SET(fd, &mut raw_fds);
         }
     }
 
-    (max_fd, raw_fds)
+    Ok((max_fd, raw_fds))
 }
 
 ///Wrapper over system `select`
@@ -644,21 +1615,25 @@ fn sockets_to_fd_set(sockets: &[&Socket]) -> (c_int, fd_set) {
 ///Returns number of sockets that are ready.
 ///
 ///If timeout isn't specified then select will be a blocking call.
-pub fn select(read_fds: &[&Socket], write_fds: &[&Socket], except_fds: &[&Socket], timeout_ms: Option<u64>) -> io::Result<c_int> {
-    let (max_read_fd, mut raw_read_fds) = sockets_to_fd_set(read_fds);
-    let (max_write_fd, mut raw_write_fds) = sockets_to_fd_set(write_fds);
-    let (max_except_fd, mut raw_except_fds) = sockets_to_fd_set(except_fds);
+///
+///Accepts any borrowed descriptors, e.g. `Socket`, `SocketRef` or `std::net::TcpStream`.
+///
+///Fails with `InvalidInput` if any descriptor is not below `FD_SETSIZE`.
+pub fn select(read_fds: &[&dyn AsFd], write_fds: &[&dyn AsFd], except_fds: &[&dyn AsFd], timeout_ms: Option<u64>) -> Result<c_int, SocketError> {
+    let (max_read_fd, mut raw_read_fds) = sockets_to_fd_set(read_fds)?;
+    let (max_write_fd, mut raw_write_fds) = sockets_to_fd_set(write_fds)?;
+    let (max_except_fd, mut raw_except_fds) = sockets_to_fd_set(except_fds)?;
 
     let nfds = cmp::max(max_read_fd, cmp::max(max_write_fd, max_except_fd)) + 1;
 
     unsafe {
         match libc::select(nfds,
-                           if max_read_fd > 0 { &mut raw_read_fds } else { ptr::null_mut() },
-                           if max_write_fd > 0 { &mut raw_write_fds } else { ptr::null_mut() },
-                           if max_except_fd > 0 { &mut raw_except_fds } else { ptr::null_mut() },
+                           if !read_fds.is_empty() { &mut raw_read_fds } else { ptr::null_mut() },
+                           if !write_fds.is_empty() { &mut raw_write_fds } else { ptr::null_mut() },
+                           if !except_fds.is_empty() { &mut raw_except_fds } else { ptr::null_mut() },
                            if let Some(timeout_ms) = timeout_ms { &mut ms_to_timeval(timeout_ms) } else { ptr::null_mut() } ) {
-            SOCKET_ERROR => Err(io::Error::last_os_error()),
-            result @ _ => Ok(result)
+            SOCKET_ERROR => Err(SocketError::last_os_error(Operation::Select)),
+            result => Ok(result)
 
         }
     }
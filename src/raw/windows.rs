@@ -3,11 +3,52 @@ use std::os::raw::*;
 use std::net;
 use std::mem;
 use std::ptr;
+use std::time;
+use std::convert::TryFrom;
 use std::sync::{Once, ONCE_INIT};
 
-#[cfg(feature = "safe_buffer_len")]
 use std::cmp;
 
+use super::{Domain, SockType, Protocol, Incoming, SocketError, Operation, ErrorClass};
+
+pub(crate) const FD_EXHAUSTED_ERRORS: &[c_int] = &[winapi::WSAEMFILE as c_int];
+
+///Names and classes of known Winsock error codes.
+pub(crate) const ERROR_NAMES: &[(c_int, &str, ErrorClass)] = &[
+    (winapi::WSAEWOULDBLOCK as c_int, "WSAEWOULDBLOCK", ErrorClass::WouldBlock),
+    (winapi::WSAEINPROGRESS as c_int, "WSAEINPROGRESS", ErrorClass::InProgress),
+    (winapi::WSAEALREADY as c_int, "WSAEALREADY", ErrorClass::InProgress),
+    (winapi::WSAEINTR as c_int, "WSAEINTR", ErrorClass::Interrupted),
+    (winapi::WSAECONNRESET as c_int, "WSAECONNRESET", ErrorClass::PeerReset),
+    (winapi::WSAENETRESET as c_int, "WSAENETRESET", ErrorClass::PeerReset),
+    (winapi::WSAECONNREFUSED as c_int, "WSAECONNREFUSED", ErrorClass::ConnectionRefused),
+    (winapi::WSAECONNABORTED as c_int, "WSAECONNABORTED", ErrorClass::ConnectionAborted),
+    (winapi::WSAENOTCONN as c_int, "WSAENOTCONN", ErrorClass::NotConnected),
+    (winapi::WSAESHUTDOWN as c_int, "WSAESHUTDOWN", ErrorClass::BrokenPipe),
+    (winapi::WSAEADDRINUSE as c_int, "WSAEADDRINUSE", ErrorClass::AddrInUse),
+    (winapi::WSAEADDRNOTAVAIL as c_int, "WSAEADDRNOTAVAIL", ErrorClass::AddrNotAvailable),
+    (winapi::WSAEACCES as c_int, "WSAEACCES", ErrorClass::PermissionDenied),
+    (winapi::WSAENETUNREACH as c_int, "WSAENETUNREACH", ErrorClass::Unreachable),
+    (winapi::WSAENETDOWN as c_int, "WSAENETDOWN", ErrorClass::Unreachable),
+    (winapi::WSAEHOSTUNREACH as c_int, "WSAEHOSTUNREACH", ErrorClass::Unreachable),
+    (winapi::WSAEHOSTDOWN as c_int, "WSAEHOSTDOWN", ErrorClass::Unreachable),
+    (winapi::WSAETIMEDOUT as c_int, "WSAETIMEDOUT", ErrorClass::TimedOut),
+    (winapi::WSAEMFILE as c_int, "WSAEMFILE", ErrorClass::Exhausted),
+    (winapi::WSAENOBUFS as c_int, "WSAENOBUFS", ErrorClass::Exhausted),
+    (winapi::WSAEINVAL as c_int, "WSAEINVAL", ErrorClass::InvalidInput),
+    (winapi::WSAEBADF as c_int, "WSAEBADF", ErrorClass::InvalidInput),
+    (winapi::WSAEFAULT as c_int, "WSAEFAULT", ErrorClass::InvalidInput),
+    (winapi::WSAENOTSOCK as c_int, "WSAENOTSOCK", ErrorClass::InvalidInput),
+    (winapi::WSAEISCONN as c_int, "WSAEISCONN", ErrorClass::InvalidInput),
+    (winapi::WSAEMSGSIZE as c_int, "WSAEMSGSIZE", ErrorClass::InvalidInput),
+    (winapi::WSAEDESTADDRREQ as c_int, "WSAEDESTADDRREQ", ErrorClass::InvalidInput),
+    (winapi::WSAEAFNOSUPPORT as c_int, "WSAEAFNOSUPPORT", ErrorClass::Unsupported),
+    (winapi::WSAEPROTONOSUPPORT as c_int, "WSAEPROTONOSUPPORT", ErrorClass::Unsupported),
+    (winapi::WSAEPROTOTYPE as c_int, "WSAEPROTOTYPE", ErrorClass::Unsupported),
+    (winapi::WSAENOPROTOOPT as c_int, "WSAENOPROTOOPT", ErrorClass::Unsupported),
+    (winapi::WSAEOPNOTSUPP as c_int, "WSAEOPNOTSUPP", ErrorClass::Unsupported),
+];
+
 mod winapi {
     #![allow(bad_style)]
     #![allow(dead_code)]
@@ -43,14 +84,52 @@ mod winapi {
         SOCK_RDM,
         SOCK_SEQPACKET,
 
-        IPPROTO_NONE,
-        IPPROTO_ICMP,
-        IPPROTO_TCP,
-        IPPROTO_UDP,
-        IPPROTO_ICMPV6,
-
+        WSAEWOULDBLOCK,
+        WSAEINPROGRESS,
+        WSAEALREADY,
+        WSAEINTR,
+        WSAECONNRESET,
+        WSAENETRESET,
+        WSAECONNREFUSED,
+        WSAECONNABORTED,
+        WSAENOTCONN,
+        WSAEISCONN,
         WSAESHUTDOWN,
+        WSAEADDRINUSE,
+        WSAEADDRNOTAVAIL,
+        WSAEACCES,
+        WSAENETUNREACH,
+        WSAENETDOWN,
+        WSAEHOSTUNREACH,
+        WSAEHOSTDOWN,
+        WSAETIMEDOUT,
+        WSAEMFILE,
+        WSAENOBUFS,
         WSAEINVAL,
+        WSAEBADF,
+        WSAEFAULT,
+        WSAENOTSOCK,
+        WSAEMSGSIZE,
+        WSAEDESTADDRREQ,
+        WSAEAFNOSUPPORT,
+        WSAEPROTONOSUPPORT,
+        WSAEPROTOTYPE,
+        WSAENOPROTOOPT,
+        WSAEOPNOTSUPP,
+
+        SOL_SOCKET,
+        SO_ERROR,
+        SO_TYPE,
+        SO_ACCEPTCONN,
+        SO_REUSEADDR,
+        SO_RCVBUF,
+        SO_SNDBUF,
+        SO_RCVTIMEO,
+        SO_SNDTIMEO,
+        IPPROTO_TCP,
+        IPPROTO_IPV6,
+        TCP_NODELAY,
+        IPV6_V6ONLY,
 
         FD_SETSIZE,
         WSADESCRIPTION_LEN,
@@ -73,6 +152,14 @@ mod winapi {
         LPWSADATA
     };
 
+    pub use self::winapi::WSAPROTOCOL_INFOW;
+
+    // Currently not available in `winapi`.
+    pub const WSA_FLAG_OVERLAPPED: winapi::DWORD = 0x01;
+    pub const WSA_FLAG_NO_HANDLE_INHERIT: winapi::DWORD = 0x80;
+    pub const SO_PROTOCOL_INFOW: winapi::c_int = 0x2005;
+    pub const FIONREAD: winapi::c_long = 0x4004667f;
+
     extern crate ws2_32;
 
     pub use self::ws2_32::{
@@ -80,6 +167,7 @@ mod winapi {
         WSACleanup,
 
         getsockname,
+        getpeername,
         socket,
         bind,
         listen,
@@ -94,7 +182,9 @@ mod winapi {
         ioctlsocket,
         shutdown,
         closesocket,
-        select
+        select,
+        WSADuplicateSocketW,
+        WSASocketW
     };
 
     extern crate kernel32;
@@ -104,68 +194,88 @@ mod winapi {
 
     pub use self::kernel32::{
     	SetHandleInformation,
-    	GetHandleInformation
+    	GetHandleInformation,
+    	GetCurrentProcessId
     };
 }
 
 macro_rules! impl_into_trait {
     ($($t:ty), +) => {
         $(
-            impl Into<c_int> for $t {
-                fn into(self) -> c_int {
-                    self as c_int
+            impl From<$t> for c_int {
+                fn from(value: $t) -> c_int {
+                    value as c_int
                 }
             }
         )+
     };
 }
 
-#[allow(non_snake_case, non_upper_case_globals)]
-///Socket family
-pub mod Family {
-    use super::{c_int, winapi};
+impl_constants!(Domain {
+    UNSPECIFIED = winapi::AF_UNSPEC => "AF_UNSPEC";
+    IPv4 = winapi::AF_INET => "AF_INET";
+    IPv6 = winapi::AF_INET6 => "AF_INET6";
+    IRDA = winapi::AF_IRDA => "AF_IRDA";
+    BTH = winapi::AF_BTH => "AF_BTH";
+});
 
-    pub const UNSPECIFIED: c_int = winapi::AF_UNSPEC;
+impl_constants!(SockType {
+    STREAM = winapi::SOCK_STREAM => "SOCK_STREAM";
+    DATAGRAM = winapi::SOCK_DGRAM => "SOCK_DGRAM";
+    RAW = winapi::SOCK_RAW => "SOCK_RAW";
+    RDM = winapi::SOCK_RDM => "SOCK_RDM";
+    SEQPACKET = winapi::SOCK_SEQPACKET => "SOCK_SEQPACKET";
+});
 
-    pub const IPv4: c_int = winapi::AF_INET;
-    pub const IPv6: c_int = winapi::AF_INET6;
-    pub const IRDA: c_int = winapi::AF_IRDA;
-    pub const BTH:  c_int = winapi::AF_BTH;
+impl SockType {
+    pub(crate) const FLAGS: &'static [(c_int, &'static str)] = &[];
 }
 
-#[allow(non_snake_case)]
-///Socket type
-pub mod Type {
-    use super::{c_int, winapi};
-
-    pub const STREAM:    c_int = winapi::SOCK_STREAM;
-    pub const DATAGRAM:  c_int = winapi::SOCK_DGRAM;
-    pub const RAW:       c_int = winapi::SOCK_RAW;
-    pub const RDM:       c_int = winapi::SOCK_RDM;
-    pub const SEQPACKET: c_int = winapi::SOCK_SEQPACKET;
-}
+impl_constants!(Protocol {
+    NONE = 0 => "IPPROTO_IP";
+    ICMPv4 = 1 => "IPPROTO_ICMP";
+    TCP = 6 => "IPPROTO_TCP";
+    UDP = 17 => "IPPROTO_UDP";
+    ICMPv6 = 58 => "IPPROTO_ICMPV6";
+    SCTP = 132 => "IPPROTO_SCTP";
+    UDPLITE = 136 => "IPPROTO_UDPLITE";
+    RAW = 255 => "IPPROTO_RAW";
+});
 
-#[allow(non_snake_case, non_upper_case_globals)]
-///Socket protocol
-pub mod Protocol {
+mod flags {
+    //bitflags 0.8 expands to `try!`
+    #![allow(deprecated)]
     use super::{c_int, winapi};
 
-    pub const NONE:   c_int = winapi::IPPROTO_NONE.0 as i32;
-    pub const ICMPv4: c_int = winapi::IPPROTO_ICMP.0 as i32;
-    pub const TCP:    c_int = winapi::IPPROTO_TCP.0 as i32;
-    pub const UDP:    c_int = winapi::IPPROTO_UDP.0 as i32;
-    pub const ICMPv6: c_int = winapi::IPPROTO_ICMPV6.0 as i32;
+    bitflags! (
+        ///Possible flags for `accept4()`
+        ///
+        ///Note that these flags correspond to emulated constants that are not represented
+        ///in the OS in this way.
+        pub flags AcceptFlags: c_int {
+            const NON_BLOCKING    = winapi::SOCK_NONBLOCK,
+            const NON_INHERITABLE = winapi::SOCK_CLOEXEC,
+        }
+    );
+
+    bitflags! (
+        ///Flags of send and receive operations.
+        pub flags MsgFlags: c_int {
+            ///Out-of-band data.
+            const OOB     = 0x1,
+            ///Receives data without removing it from queue.
+            const PEEK    = 0x2,
+            ///Waits until whole buffer is filled.
+            const WAITALL = 0x8,
+            ///Datagram was longer than buffer.
+            const TRUNC   = 0x0100,
+            ///Control data was discarded due to lack of space.
+            const CTRUNC  = 0x0200,
+        }
+    );
 }
 
-#[allow(non_snake_case)]
-///Possible flags for `accept4()`
-///
-///Note that these flags correspond to emulated constants that are not represented
-///in the OS in this way.
-bitflags! (pub flags AcceptFlags: c_int {
-    const NON_BLOCKING    = winapi::SOCK_NONBLOCK,
-    const NON_INHERITABLE = winapi::SOCK_CLOEXEC,
-});
+pub use self::flags::*;
 
 #[repr(i32)]
 #[derive(Copy, Clone)]
@@ -189,18 +299,31 @@ pub struct Socket {
 impl Socket {
     ///Initializes new socket.
     ///
-    ///Corresponds to C connect()
-    pub fn new(family: c_int, _type: c_int, protocol: c_int) -> io::Result<Socket> {
-        static INIT: Once = ONCE_INIT;
+    ///Corresponds to C socket()
+    ///
+    ///Socket is not inheritable by child processes.
+    ///Use `new_inheritable` to opt-out.
+    pub fn new(domain: Domain, ty: SockType, protocol: Protocol) -> Result<Socket, SocketError> {
+        Socket::init();
 
-        INIT.call_once(|| {
-            //just to initialize winsock inside libstd
-            let _ = net::UdpSocket::bind("127.0.0.1:34254");
-        });
+        unsafe {
+            match winapi::WSASocketW(domain.into(), ty.into(), protocol.into(), ptr::null_mut(), 0,
+                                     winapi::WSA_FLAG_OVERLAPPED | winapi::WSA_FLAG_NO_HANDLE_INHERIT) {
+                winapi::INVALID_SOCKET => Err(SocketError::last_os_error(Operation::Socket)),
+                fd => Ok(Socket {
+                    inner: fd
+                }),
+            }
+        }
+    }
+
+    ///Initializes new socket that is inherited by child processes.
+    pub fn new_inheritable(domain: Domain, ty: SockType, protocol: Protocol) -> Result<Socket, SocketError> {
+        Socket::init();
 
         unsafe {
-            match winapi::socket(family, _type, protocol) {
-                winapi::INVALID_SOCKET => Err(io::Error::last_os_error()),
+            match winapi::socket(domain.into(), ty.into(), protocol.into()) {
+                winapi::INVALID_SOCKET => Err(SocketError::last_os_error(Operation::Socket)),
                 fd => Ok(Socket {
                     inner: fd
                 }),
@@ -208,6 +331,15 @@ impl Socket {
         }
     }
 
+    fn init() {
+        static INIT: Once = ONCE_INIT;
+
+        INIT.call_once(|| {
+            //just to initialize winsock inside libstd
+            let _ = net::UdpSocket::bind("127.0.0.1:34254");
+        });
+    }
+
     ///Returns underlying socket descriptor.
     ///
     ///Note: ownership is not transferred.
@@ -220,36 +352,53 @@ impl Socket {
     ///Wraps `getsockname()`
     ///
     ///Available for binded/connected sockets.
-    pub fn name(&self) -> io::Result<net::SocketAddr> {
+    pub fn name(&self) -> Result<net::SocketAddr, SocketError> {
         unsafe {
             let mut storage: winapi::SOCKADDR_STORAGE_LH = mem::zeroed();
             let mut len = mem::size_of_val(&storage) as c_int;
 
             match winapi::getsockname(self.inner, &mut storage as *mut _ as *mut _, &mut len) {
-                winapi::SOCKET_ERROR => Err(io::Error::last_os_error()),
-                _ => sockaddr_to_addr(&storage, len)
+                winapi::SOCKET_ERROR => Err(SocketError::last_os_error(Operation::GetName)),
+                _ => sockaddr_to_addr(&storage, len).map_err(|error| SocketError::new(Operation::GetName, error))
+            }
+        }
+    }
+
+    ///Retrieves address of connected peer.
+    ///
+    ///Wraps `getpeername()`
+    ///
+    ///Fails with `NotConnected` until connection is established.
+    pub fn peer_name(&self) -> Result<net::SocketAddr, SocketError> {
+        unsafe {
+            let mut storage: winapi::SOCKADDR_STORAGE_LH = mem::zeroed();
+            let mut len = mem::size_of_val(&storage) as c_int;
+
+            match winapi::getpeername(self.inner, &mut storage as *mut _ as *mut _, &mut len) {
+                winapi::SOCKET_ERROR => Err(SocketError::last_os_error(Operation::GetPeerName)),
+                _ => sockaddr_to_addr(&storage, len).map_err(|error| SocketError::new(Operation::GetPeerName, error))
             }
         }
     }
 
     ///Binds socket to address.
-    pub fn bind(&self, addr: &net::SocketAddr) -> io::Result<()> {
-        let (addr, len) = get_raw_addr(addr);
+    pub fn bind(&self, addr: &net::SocketAddr) -> Result<(), SocketError> {
+        let (raw_addr, len) = get_raw_addr(addr);
 
         unsafe {
-            match winapi::bind(self.inner, addr, len) {
+            match winapi::bind(self.inner, &raw_addr as *const _ as *const _, len) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Bind).with_addr(*addr))
             }
         }
     }
 
     ///Listens for incoming connections on this socket.
-    pub fn listen(&self, backlog: c_int) -> io::Result<()> {
+    pub fn listen(&self, backlog: c_int) -> Result<(), SocketError> {
         unsafe {
             match winapi::listen(self.inner, backlog) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Listen))
             }
         }
     }
@@ -257,25 +406,15 @@ impl Socket {
     ///Receives some bytes from socket
     ///
     ///Number of received bytes is returned on success
-    pub fn recv(&self, buf: &mut [u8], flags: c_int) -> io::Result<usize> {
+    pub fn recv(&self, buf: &mut [u8], flags: MsgFlags) -> Result<usize, SocketError> {
         #[cfg(feature = "safe_buffer_len")]
         let len = cmp::min(buf.len(), i32::max_value() as usize) as i32;
         #[cfg(not(feature = "safe_buffer_len"))]
         let len = buf.len() as i32;
 
         unsafe {
-            match winapi::recv(self.inner, buf.as_mut_ptr() as *mut c_char, len, flags) {
-                -1 => {
-                    let error = io::Error::last_os_error();
-                    let raw_code = error.raw_os_error().unwrap();
-
-                    if raw_code == winapi::WSAESHUTDOWN as i32 {
-                        Ok(0)
-                    }
-                    else {
-                        Err(error)
-                    }
-                },
+            match winapi::recv(self.inner, buf.as_mut_ptr() as *mut c_char, len, flags.bits()) {
+                -1 => Err(SocketError::last_os_error(Operation::Recv)),
                 n => Ok(n as usize)
             }
         }
@@ -284,7 +423,7 @@ impl Socket {
     ///Receives some bytes from socket
     ///
     ///Number of received bytes and remote address are returned on success.
-    pub fn recv_from(&self, buf: &mut [u8], flags: c_int) -> io::Result<(usize, net::SocketAddr)> {
+    pub fn recv_from(&self, buf: &mut [u8], flags: MsgFlags) -> Result<(usize, net::SocketAddr), SocketError> {
         #[cfg(feature = "safe_buffer_len")]
         let len = cmp::min(buf.len(), i32::max_value() as usize) as i32;
         #[cfg(not(feature = "safe_buffer_len"))]
@@ -294,21 +433,10 @@ impl Socket {
             let mut storage: winapi::SOCKADDR_STORAGE_LH = mem::zeroed();
             let mut storage_len = mem::size_of_val(&storage) as c_int;
 
-            match winapi::recvfrom(self.inner, buf.as_mut_ptr() as *mut c_char, len, flags, &mut storage as *mut _ as *mut _, &mut storage_len) {
-                -1 => {
-                    let error = io::Error::last_os_error();
-                    let raw_code = error.raw_os_error().unwrap();
-
-                    if raw_code == winapi::WSAESHUTDOWN as i32 {
-                        let peer_addr = sockaddr_to_addr(&storage, storage_len)?;
-                        Ok((0, peer_addr))
-                    }
-                    else {
-                        Err(error)
-                    }
-                },
+            match winapi::recvfrom(self.inner, buf.as_mut_ptr() as *mut c_char, len, flags.bits(), &mut storage as *mut _ as *mut _, &mut storage_len) {
+                -1 => Err(SocketError::last_os_error(Operation::Recv)),
                 n => {
-                    let peer_addr = sockaddr_to_addr(&storage, storage_len)?;
+                    let peer_addr = sockaddr_to_addr(&storage, storage_len).map_err(|error| SocketError::new(Operation::Recv, error))?;
                     Ok((n as usize, peer_addr))
                 }
             }
@@ -318,25 +446,15 @@ impl Socket {
     ///Sends some bytes through socket.
     ///
     ///Number of sent bytes is returned.
-    pub fn send(&self, buf: &[u8], flags: c_int) -> io::Result<usize> {
+    pub fn send(&self, buf: &[u8], flags: MsgFlags) -> Result<usize, SocketError> {
         #[cfg(feature = "safe_buffer_len")]
         let len = cmp::min(buf.len(), i32::max_value() as usize) as i32;
         #[cfg(not(feature = "safe_buffer_len"))]
         let len = buf.len() as i32;
 
         unsafe {
-            match winapi::send(self.inner, buf.as_ptr() as *const c_char, len, flags) {
-                -1 => {
-                    let error = io::Error::last_os_error();
-                    let raw_code = error.raw_os_error().unwrap();
-
-                    if raw_code == winapi::WSAESHUTDOWN as i32 {
-                        Ok(0)
-                    }
-                    else {
-                        Err(error)
-                    }
-                },
+            match winapi::send(self.inner, buf.as_ptr() as *const c_char, len, flags.bits()) {
+                -1 => Err(SocketError::last_os_error(Operation::Send)),
                 n => Ok(n as usize)
             }
         }
@@ -348,7 +466,7 @@ impl Socket {
     ///
     ///Note: the socket will be bound, if it isn't already.
     ///Use method `name` to determine address.
-    pub fn send_to(&self, buf: &[u8], peer_addr: &net::SocketAddr, flags: c_int) -> io::Result<usize> {
+    pub fn send_to(&self, buf: &[u8], peer_addr: &net::SocketAddr, flags: MsgFlags) -> Result<usize, SocketError> {
         #[cfg(feature = "safe_buffer_len")]
         let len = cmp::min(buf.len(), i32::max_value() as usize) as i32;
         #[cfg(not(feature = "safe_buffer_len"))]
@@ -356,18 +474,8 @@ impl Socket {
         let (addr, addr_len) = get_raw_addr(peer_addr);
 
         unsafe {
-            match winapi::sendto(self.inner, buf.as_ptr() as *const c_char, len, flags, addr, addr_len) {
-                -1 => {
-                    let error = io::Error::last_os_error();
-                    let raw_code = error.raw_os_error().unwrap();
-
-                    if raw_code == winapi::WSAESHUTDOWN as i32 {
-                        Ok(0)
-                    }
-                    else {
-                        Err(error)
-                    }
-                },
+            match winapi::sendto(self.inner, buf.as_ptr() as *const c_char, len, flags.bits(), &addr as *const _ as *const _, addr_len) {
+                -1 => Err(SocketError::last_os_error(Operation::Send).with_addr(*peer_addr)),
                 n => Ok(n as usize)
             }
         }
@@ -381,46 +489,122 @@ impl Socket {
     ///
     /// * `AcceptFlags::NON_BLOCKING`    – Mark the newly created socket as non-blocking
     /// * `AcceptFlags::NON_INHERITABLE` – Mark the newly created socket as not inheritable by client processes
-    pub fn accept4(&self, flags: AcceptFlags) -> io::Result<(Socket, net::SocketAddr)> {
-        self.accept().map(|(sock, addr)| {
-            // Emulate the two most common (and useful) `accept4` flags
-            sock.set_blocking(!flags.contains(NON_BLOCKING)).expect("Setting newly obtained client socket blocking mode");
-            sock.set_inheritable(!flags.contains(NON_INHERITABLE)).expect("Setting newly obtained client socket inheritance mode");
+    pub fn accept4(&self, flags: AcceptFlags) -> Result<(Socket, net::SocketAddr), SocketError> {
+        let (sock, addr) = self.accept_raw()?;
+
+        // Emulate the two most common (and useful) `accept4` flags
+        //
+        // On failure accepted socket is dropped, closing its handle.
+        sock.set_blocking(!flags.contains(NON_BLOCKING))?;
+        sock.set_inheritable(!flags.contains(NON_INHERITABLE))?;
 
-            (sock, addr)
-        })
+        Ok((sock, addr))
     }
 
     ///Accepts incoming connection.
-    pub fn accept(&self) -> io::Result<(Socket, net::SocketAddr)> {
+    ///
+    ///Accepted socket is not inheritable by child processes, which is the same as
+    ///`.accept4(NON_INHERITABLE)`. Use `.accept4(AcceptFlags::empty())` to opt-out.
+    pub fn accept(&self) -> Result<(Socket, net::SocketAddr), SocketError> {
+        let (sock, addr) = self.accept_raw()?;
+        sock.set_inheritable(false)?;
+        Ok((sock, addr))
+    }
+
+    fn accept_raw(&self) -> Result<(Socket, net::SocketAddr), SocketError> {
         unsafe {
             let mut storage: winapi::SOCKADDR_STORAGE_LH = mem::zeroed();
             let mut len = mem::size_of_val(&storage) as c_int;
 
             match winapi::accept(self.inner, &mut storage as *mut _ as *mut _, &mut len) {
-                winapi::INVALID_SOCKET => Err(io::Error::last_os_error()),
-                sock @ _ => {
-                    let addr = sockaddr_to_addr(&storage, len)?;
-                    Ok((Socket { inner: sock }, addr))
+                winapi::INVALID_SOCKET => Err(SocketError::last_os_error(Operation::Accept)),
+                sock => {
+                    let sock = Socket { inner: sock };
+                    let addr = sockaddr_to_addr(&storage, len).map_err(|error| SocketError::new(Operation::Accept, error))?;
+                    Ok((sock, addr))
                 }
             }
         }
     }
 
+    ///Returns iterator over incoming connections, accepted with `flags`.
+    ///
+    ///See `Incoming` for details.
+    pub fn incoming(&self, flags: AcceptFlags) -> Incoming<'_> {
+        Incoming::new(self, flags)
+    }
+
     ///Connects socket with remote address.
-    pub fn connect(&self, addr: &net::SocketAddr) -> io::Result<()> {
-        let (addr, len) = get_raw_addr(addr);
+    pub fn connect(&self, addr: &net::SocketAddr) -> Result<(), SocketError> {
+        let (raw_addr, len) = get_raw_addr(addr);
 
         unsafe {
-            match winapi::connect(self.inner, addr, len) {
+            match winapi::connect(self.inner, &raw_addr as *const _ as *const _, len) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Connect).with_addr(*addr))
             }
         }
     }
 
+    ///Connects socket with remote address, giving up once timeout expires.
+    ///
+    ///Socket is switched into non-blocking mode for the duration of connect.
+    ///
+    ///Returns error of kind `TimedOut` if connection is not established in time.
+    ///
+    ///## Note:
+    ///
+    ///Windows provides no way to query blocking mode so socket is always left in blocking mode.
+    pub fn connect_timeout(&self, addr: &net::SocketAddr, timeout: time::Duration) -> Result<(), SocketError> {
+        if timeout == time::Duration::from_secs(0) {
+            return Err(SocketError::custom(Operation::Connect, io::ErrorKind::InvalidInput, "cannot set a 0 duration timeout").with_addr(*addr));
+        }
+
+        self.set_blocking(false)?;
+        let result = self.connect_wait(addr, timeout);
+
+        //Failure to restore mode must not hide outcome of connect.
+        let restored = self.set_blocking(true);
+        if result.is_ok() {
+            restored?;
+        }
+
+        result
+    }
+
+    fn connect_wait(&self, addr: &net::SocketAddr, timeout: time::Duration) -> Result<(), SocketError> {
+        match self.connect(addr) {
+            Ok(()) => return Ok(()),
+            Err(ref error) if error.raw_os_error() == Some(winapi::WSAEWOULDBLOCK as i32) => (),
+            Err(error) => return Err(error)
+        }
+
+        //Round up so that sub-millisecond timeout is not turned into immediate failure.
+        let timeout_ms = timeout.as_secs().saturating_mul(1_000) + (timeout.subsec_nanos() as u64).div_ceil(1_000_000);
+
+        //Failed connect is reported through except set.
+        if select(&[], &[self], &[self], Some(timeout_ms))? == 0 {
+            return Err(SocketError::custom(Operation::Connect, io::ErrorKind::TimedOut, "connection timed out").with_addr(*addr));
+        }
+
+        match self.take_error()? {
+            Some(error) => Err(error.with_addr(*addr)),
+            None => Ok(())
+        }
+    }
+
+    ///Retrieves and clears pending error of socket i.e. `SO_ERROR`.
+    ///
+    ///Outcome of non-blocking connect is reported this way, therefore error is attributed to `Operation::Connect`.
+    pub fn take_error(&self) -> Result<Option<SocketError>, SocketError> {
+        match self.get_opt::<c_int>(winapi::SOL_SOCKET, winapi::SO_ERROR)? {
+            0 => Ok(None),
+            code => Ok(Some(SocketError::from_raw_os_error(Operation::Connect, code)))
+        }
+    }
+
     ///Retrieves socket option.
-    pub fn get_opt<T>(&self, level: c_int, name: c_int) -> io::Result<T> {
+    pub fn get_opt<T>(&self, level: c_int, name: c_int) -> Result<T, SocketError> {
         unsafe {
             let mut value: T = mem::zeroed();
             let value_ptr = &mut value as *mut T as *mut c_char;
@@ -428,7 +612,7 @@ impl Socket {
 
             match winapi::getsockopt(self.inner, level, name, value_ptr, &mut value_len) {
                 0 => Ok(value),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::GetOpt { level, name }))
             }
         }
     }
@@ -436,13 +620,13 @@ impl Socket {
     ///Sets socket option
     ///
     ///Value is generally integer or C struct.
-    pub fn set_opt<T>(&self, level: c_int, name: c_int, value: T) -> io::Result<()> {
+    pub fn set_opt<T>(&self, level: c_int, name: c_int, value: T) -> Result<(), SocketError> {
         unsafe {
             let value = &value as *const T as *const c_char;
 
             match winapi::setsockopt(self.inner, level, name, value, mem::size_of::<T>() as c_int) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::SetOpt { level, name }))
             }
         }
     }
@@ -450,86 +634,209 @@ impl Socket {
     ///Sets I/O parameters of socket.
     ///
     ///It uses `ioctlsocket` under hood.
-    pub fn ioctl(&self, request: c_int, value: c_ulong) -> io::Result<()> {
+    pub fn ioctl(&self, request: c_int, value: c_ulong) -> Result<(), SocketError> {
         unsafe {
             let mut value = value;
             let value = &mut value as *mut c_ulong;
 
             match winapi::ioctlsocket(self.inner, request, value) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Ioctl))
             }
         }
     }
 
     ///Sets non-blocking mode.
-    pub fn set_blocking(&self, value: bool) -> io::Result<()> {
+    pub fn set_blocking(&self, value: bool) -> Result<(), SocketError> {
         self.ioctl(winapi::FIONBIO as c_int, (!value) as c_ulong)
     }
 
+    ///Returns number of bytes that can be received without blocking i.e. `FIONREAD`.
+    pub fn bytes_available(&self) -> Result<usize, SocketError> {
+        unsafe {
+            let mut value: c_ulong = 0;
+
+            match winapi::ioctlsocket(self.inner, winapi::FIONREAD as c_int, &mut value) {
+                0 => Ok(value as usize),
+                _ => Err(SocketError::last_os_error(Operation::Ioctl))
+            }
+        }
+    }
+
     ///Sets whether this socket will be inherited by child processes or not.
     ///
     ///Internally this implemented by calling `SetHandleInformation(sock, HANDLE_FLAG_INHERIT, …)`.
-    pub fn set_inheritable(&self, value: bool) -> io::Result<()> {
+    pub fn set_inheritable(&self, value: bool) -> Result<(), SocketError> {
         unsafe {
             let flag = if value { winapi::HANDLE_FLAG_INHERIT } else { 0 };
             match winapi::SetHandleInformation(self.inner as winapi::HANDLE, winapi::HANDLE_FLAG_INHERIT, flag) {
-                0 => Err(io::Error::last_os_error()),
+                0 => Err(SocketError::last_os_error(Operation::Fcntl)),
                 _ => Ok(())
             }
         }
     }
 
 	///Returns whether this socket will be inherited by child processes or not.
-	pub fn get_inheritable(&self) -> io::Result<bool> {
+	pub fn get_inheritable(&self) -> Result<bool, SocketError> {
 		unsafe {
 			let mut flags: winapi::DWORD = 0;
 			match winapi::GetHandleInformation(self.inner as winapi::HANDLE, &mut flags as *mut _) {
-                0 => Err(io::Error::last_os_error()),
+                0 => Err(SocketError::last_os_error(Operation::Fcntl)),
                 _ => Ok((flags & winapi::HANDLE_FLAG_INHERIT) != 0)
             }
         }
 	}
 
+    ///Verifies that socket is of type `ty` and one of `families`.
+    fn check_kind(&self, ty: c_int, families: &[c_int], listening: bool) -> Result<(), SocketError> {
+        if self.get_opt::<c_int>(winapi::SOL_SOCKET, winapi::SO_TYPE)? != ty {
+            return Err(SocketError::custom(Operation::GetOpt { level: winapi::SOL_SOCKET, name: winapi::SO_TYPE }, io::ErrorKind::InvalidInput, "socket type mismatch"));
+        }
+
+        if (self.get_opt::<c_int>(winapi::SOL_SOCKET, winapi::SO_ACCEPTCONN)? != 0) != listening {
+            return Err(SocketError::custom(Operation::GetOpt { level: winapi::SOL_SOCKET, name: winapi::SO_ACCEPTCONN }, io::ErrorKind::InvalidInput, "socket listening state mismatch"));
+        }
+
+        let info = self.get_opt::<winapi::WSAPROTOCOL_INFOW>(winapi::SOL_SOCKET, winapi::SO_PROTOCOL_INFOW)?;
+        if !families.contains(&info.iAddressFamily) {
+            return Err(SocketError::custom(Operation::GetOpt { level: winapi::SOL_SOCKET, name: winapi::SO_PROTOCOL_INFOW }, io::ErrorKind::InvalidInput, "socket family mismatch"));
+        }
+
+        Ok(())
+    }
+
+    ///Creates new handle for the same underlying socket.
+    ///
+    ///Returned socket is not inheritable, regardless of whether original one is.
+    pub fn try_clone(&self) -> Result<Socket, SocketError> {
+        unsafe {
+            let mut info: winapi::WSAPROTOCOL_INFOW = mem::zeroed();
+
+            if winapi::WSADuplicateSocketW(self.inner, winapi::GetCurrentProcessId(), &mut info) != 0 {
+                return Err(SocketError::last_os_error(Operation::Dup));
+            }
+
+            match winapi::WSASocketW(info.iAddressFamily, info.iSocketType, info.iProtocol, &mut info, 0,
+                                     winapi::WSA_FLAG_OVERLAPPED | winapi::WSA_FLAG_NO_HANDLE_INHERIT) {
+                winapi::INVALID_SOCKET => Err(SocketError::last_os_error(Operation::Dup)),
+                socket => Ok(Socket { inner: socket })
+            }
+        }
+    }
+
+    ///Sets `SO_REUSEADDR` option.
+    pub fn set_reuse_address(&self, value: bool) -> Result<(), SocketError> {
+        self.set_opt(winapi::SOL_SOCKET, winapi::SO_REUSEADDR, value as c_int)
+    }
+
+    ///Sets `TCP_NODELAY` option.
+    pub fn set_nodelay(&self, value: bool) -> Result<(), SocketError> {
+        self.set_opt(winapi::IPPROTO_TCP.0 as c_int, winapi::TCP_NODELAY, value as c_int)
+    }
+
+    ///Sets size of receive buffer i.e. `SO_RCVBUF` option.
+    pub fn set_recv_buffer_size(&self, size: usize) -> Result<(), SocketError> {
+        self.set_opt(winapi::SOL_SOCKET, winapi::SO_RCVBUF, cmp::min(size, c_int::MAX as usize) as c_int)
+    }
+
+    ///Sets size of send buffer i.e. `SO_SNDBUF` option.
+    pub fn set_send_buffer_size(&self, size: usize) -> Result<(), SocketError> {
+        self.set_opt(winapi::SOL_SOCKET, winapi::SO_SNDBUF, cmp::min(size, c_int::MAX as usize) as c_int)
+    }
+
+    ///Sets `IPV6_V6ONLY` option.
+    ///
+    ///Note: it must be set before socket is bound.
+    pub fn set_only_v6(&self, value: bool) -> Result<(), SocketError> {
+        self.set_opt(winapi::IPPROTO_IPV6.0 as c_int, winapi::IPV6_V6ONLY, value as c_int)
+    }
+
+    ///Sets timeout of receive operations i.e. `SO_RCVTIMEO` option.
+    ///
+    ///`None` means no timeout.
+    pub fn set_read_timeout(&self, timeout: Option<time::Duration>) -> Result<(), SocketError> {
+        self.set_timeout(winapi::SO_RCVTIMEO, timeout)
+    }
+
+    ///Sets timeout of send operations i.e. `SO_SNDTIMEO` option.
+    ///
+    ///`None` means no timeout.
+    pub fn set_write_timeout(&self, timeout: Option<time::Duration>) -> Result<(), SocketError> {
+        self.set_timeout(winapi::SO_SNDTIMEO, timeout)
+    }
+
+    fn set_timeout(&self, name: c_int, timeout: Option<time::Duration>) -> Result<(), SocketError> {
+        let timeout = duration_to_ms(timeout).map_err(|error| SocketError::new(Operation::SetOpt { level: winapi::SOL_SOCKET, name }, error))?;
+        self.set_opt(winapi::SOL_SOCKET, name, timeout)
+    }
+
     ///Stops receive and/or send over socket.
-    pub fn shutdown(&self, direction: ShutdownType) -> io::Result<()> {
+    pub fn shutdown(&self, direction: ShutdownType) -> Result<(), SocketError> {
         unsafe {
             match winapi::shutdown(self.inner, direction.into()) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Shutdown))
             }
         }
     }
 
-    ///Closes socket.
+    ///Closes socket, reporting error of `closesocket()`.
     ///
-    ///Note: on `Drop` socket will be closed on its own.
-    ///There is no need to close it explicitly.
-    pub fn close(&self) -> io::Result<()> {
+    ///Note: on `Drop` socket will be closed on its own, but any error is ignored.
+    pub fn close(self) -> Result<(), SocketError> {
+        let socket = self.into_raw_socket();
+
         unsafe {
-            match winapi::closesocket(self.inner) {
+            match winapi::closesocket(socket) {
                 0 => Ok(()),
-                _ => Err(io::Error::last_os_error())
+                _ => Err(SocketError::last_os_error(Operation::Close))
             }
         }
     }
 }
 
-fn get_raw_addr(addr: &net::SocketAddr) -> (*const winapi::SOCKADDR, c_int) {
-    match *addr {
+///Converts address into C `SOCKADDR_STORAGE` and its length.
+pub(crate) fn get_raw_addr(addr: &net::SocketAddr) -> (winapi::SOCKADDR_STORAGE_LH, c_int) {
+    //std's address types are not guaranteed to share layout with C structs
+    //so we need to fill `SOCKADDR_*` ourselves.
+    let mut storage: winapi::SOCKADDR_STORAGE_LH = unsafe { mem::zeroed() };
+
+    let len = match *addr {
         net::SocketAddr::V4(ref a) => {
-            (a as *const _ as *const _, mem::size_of_val(a) as c_int)
+            let raw = unsafe { &mut *(&mut storage as *mut _ as *mut winapi::SOCKADDR_IN) };
+            raw.sin_family = winapi::AF_INET as winapi::ADDRESS_FAMILY;
+            raw.sin_port = a.port().to_be();
+            raw.sin_addr.S_un = u32::from_ne_bytes(a.ip().octets());
+
+            mem::size_of::<winapi::SOCKADDR_IN>()
         }
         net::SocketAddr::V6(ref a) => {
-            (a as *const _ as *const _, mem::size_of_val(a) as c_int)
+            let raw = unsafe { &mut *(&mut storage as *mut _ as *mut winapi::sockaddr_in6) };
+            raw.sin6_family = winapi::AF_INET6 as c_short;
+            raw.sin6_port = a.port().to_be();
+            raw.sin6_flowinfo = a.flowinfo() as c_ulong;
+            raw.sin6_addr.s6_addr = a.ip().octets();
+            raw.sin6_scope_id = a.scope_id() as c_ulong;
+
+            mem::size_of::<winapi::sockaddr_in6>()
         }
-    }
+    };
+
+    (storage, len as c_int)
 }
 
-fn sockaddr_to_addr(storage: &winapi::SOCKADDR_STORAGE_LH, len: c_int) -> io::Result<net::SocketAddr> {
+///Converts C `SOCKADDR_STORAGE` of length `len` into address.
+///
+///Fails with `InvalidInput` if address is not of IP family or `len` is too short for it.
+pub(crate) fn sockaddr_to_addr(storage: &winapi::SOCKADDR_STORAGE_LH, len: c_int) -> io::Result<net::SocketAddr> {
+    //Negative length must not turn into huge one.
+    let len = cmp::max(len, 0) as usize;
+
     match storage.ss_family as c_int {
         winapi::AF_INET => {
-            assert!(len as usize >= mem::size_of::<winapi::SOCKADDR_IN>());
+            if len < mem::size_of::<winapi::SOCKADDR_IN>() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid addr length."));
+            }
             let storage = unsafe { *(storage as *const _ as *const winapi::SOCKADDR_IN) };
             let address = unsafe { storage.sin_addr.S_un_b() };
             let ip = net::Ipv4Addr::new(address.s_b1,
@@ -542,9 +849,11 @@ fn sockaddr_to_addr(storage: &winapi::SOCKADDR_STORAGE_LH, len: c_int) -> io::Re
             Ok(net::SocketAddr::V4(net::SocketAddrV4::new(ip, storage.sin_port.to_be())))
         }
         winapi::AF_INET6 => {
-            assert!(len as usize >= mem::size_of::<winapi::sockaddr_in6>());
+            if len < mem::size_of::<winapi::sockaddr_in6>() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid addr length."));
+            }
             let storage = unsafe { *(storage as *const _ as *const winapi::sockaddr_in6) };
-            let ip = net::Ipv6Addr::from(storage.sin6_addr.s6_addr.clone());
+            let ip = net::Ipv6Addr::from(storage.sin6_addr.s6_addr);
 
             Ok(net::SocketAddr::V6(net::SocketAddrV6::new(ip, storage.sin6_port.to_be(), storage.sin6_flowinfo, storage.sin6_scope_id)))
         }
@@ -554,10 +863,33 @@ fn sockaddr_to_addr(storage: &winapi::SOCKADDR_STORAGE_LH, len: c_int) -> io::Re
     }
 }
 
+#[doc(hidden)]
+///Address conversions exposed for fuzz targets and tests, not part of public API.
+pub mod fuzzing {
+    use std::io;
+    use std::net;
+    use std::os::raw::c_int;
+
+    use super::winapi::SOCKADDR_STORAGE_LH;
+
+    pub fn get_raw_addr(addr: &net::SocketAddr) -> (SOCKADDR_STORAGE_LH, c_int) {
+        super::get_raw_addr(addr)
+    }
+
+    pub fn sockaddr_to_addr(storage: &SOCKADDR_STORAGE_LH, len: c_int) -> io::Result<net::SocketAddr> {
+        super::sockaddr_to_addr(storage, len)
+    }
+}
+
 impl Drop for Socket {
+    ///Closes socket.
+    ///
+    ///Socket is not shut down, so connection stays alive for other holders of it.
+    ///Use `ShutdownOnDrop` if that is desired.
     fn drop(&mut self) {
-        let _ = self.shutdown(ShutdownType::Both);
-        let _ = self.close();
+        unsafe {
+            winapi::closesocket(self.inner);
+        }
     }
 }
 
@@ -565,6 +897,9 @@ use std::os::windows::io::{
     AsRawSocket,
     FromRawSocket,
     IntoRawSocket,
+    AsSocket,
+    BorrowedSocket,
+    OwnedSocket
 };
 
 impl AsRawSocket for Socket {
@@ -588,6 +923,19 @@ impl IntoRawSocket for Socket {
 }
 
 #[inline]
+fn duration_to_ms(timeout: Option<time::Duration>) -> io::Result<winapi::DWORD> {
+    match timeout {
+        None => Ok(0),
+        Some(timeout) if timeout == time::Duration::from_secs(0) => {
+            Err(io::Error::new(io::ErrorKind::InvalidInput, "cannot set a 0 duration timeout"))
+        },
+        Some(timeout) => {
+            let timeout_ms = timeout.as_secs().saturating_mul(1_000) + (timeout.subsec_nanos() as u64).div_ceil(1_000_000);
+            Ok(cmp::min(timeout_ms, winapi::DWORD::MAX as u64) as winapi::DWORD)
+        }
+    }
+}
+
 fn ms_to_timeval(timeout_ms: u64) -> winapi::timeval {
     winapi::timeval {
         tv_sec: timeout_ms as c_long / 1000,
@@ -595,13 +943,13 @@ fn ms_to_timeval(timeout_ms: u64) -> winapi::timeval {
     }
 }
 
-fn sockets_to_fd_set(sockets: &[&Socket]) -> winapi::fd_set {
+fn sockets_to_fd_set(sockets: &[&dyn AsSocket]) -> winapi::fd_set {
     assert!(sockets.len() < winapi::FD_SETSIZE);
     let mut raw_fds: winapi::fd_set = unsafe { mem::zeroed() };
 
     for socket in sockets {
         let idx = raw_fds.fd_count as usize;
-        raw_fds.fd_array[idx] = socket.inner;
+        raw_fds.fd_array[idx] = socket.as_socket().as_raw_socket();
         raw_fds.fd_count += 1;
     }
 
@@ -621,7 +969,9 @@ fn sockets_to_fd_set(sockets: &[&Socket]) -> winapi::fd_set {
 ///## Warning:
 ///
 ///It is invalid to pass all sets of descriptors empty on Windows.
-pub fn select(read_fds: &[&Socket], write_fds: &[&Socket], except_fds: &[&Socket], timeout_ms: Option<u64>) -> io::Result<c_int> {
+///
+///Accepts any borrowed sockets, e.g. `Socket`, `SocketRef` or `std::net::TcpStream`.
+pub fn select(read_fds: &[&dyn AsSocket], write_fds: &[&dyn AsSocket], except_fds: &[&dyn AsSocket], timeout_ms: Option<u64>) -> Result<c_int, SocketError> {
     let mut raw_read_fds = sockets_to_fd_set(read_fds);
     let mut raw_write_fds = sockets_to_fd_set(write_fds);
     let mut raw_except_fds = sockets_to_fd_set(except_fds);
@@ -632,9 +982,55 @@ pub fn select(read_fds: &[&Socket], write_fds: &[&Socket], except_fds: &[&Socket
                              if write_fds.len() > 0 { &mut raw_write_fds } else { ptr::null_mut() },
                              if except_fds.len() > 0 { &mut raw_except_fds } else { ptr::null_mut() },
                              if let Some(timeout_ms) = timeout_ms { &ms_to_timeval(timeout_ms) } else { ptr::null() } ) {
-            winapi::SOCKET_ERROR => Err(io::Error::last_os_error()),
-            result @ _ => Ok(result)
+            winapi::SOCKET_ERROR => Err(SocketError::last_os_error(Operation::Select)),
+            result => Ok(result)
 
         }
     }
 }
+
+impl AsSocket for Socket {
+    fn as_socket(&self) -> BorrowedSocket<'_> {
+        unsafe { BorrowedSocket::borrow_raw(self.inner) }
+    }
+}
+
+impl From<OwnedSocket> for Socket {
+    fn from(socket: OwnedSocket) -> Socket {
+        Socket { inner: socket.into_raw_socket() }
+    }
+}
+
+impl From<Socket> for OwnedSocket {
+    fn from(socket: Socket) -> OwnedSocket {
+        unsafe { OwnedSocket::from_raw_socket(socket.into_raw_socket()) }
+    }
+}
+
+macro_rules! impl_std_conversions {
+    ($($t:ty => $ty:expr, $listening:expr;)+) => {
+        $(
+            impl From<$t> for Socket {
+                fn from(socket: $t) -> Socket {
+                    Socket { inner: socket.into_raw_socket() }
+                }
+            }
+
+            ///Fails with `InvalidInput` if socket's type, family or listening state doesn't match.
+            impl TryFrom<Socket> for $t {
+                type Error = SocketError;
+
+                fn try_from(socket: Socket) -> Result<$t, SocketError> {
+                    socket.check_kind($ty, &[winapi::AF_INET, winapi::AF_INET6], $listening)?;
+                    Ok(unsafe { <$t>::from_raw_socket(socket.into_raw_socket()) })
+                }
+            }
+        )+
+    };
+}
+
+impl_std_conversions!(
+    net::TcpStream => winapi::SOCK_STREAM, false;
+    net::TcpListener => winapi::SOCK_STREAM, true;
+    net::UdpSocket => winapi::SOCK_DGRAM, false;
+);
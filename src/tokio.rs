@@ -0,0 +1,240 @@
+//! Tokio integration.
+//!
+//! Provides `AsyncSocket` that drives `raw::Socket` through tokio's reactor.
+//!
+//! Configure socket as usual, then hand it over to `AsyncSocket::new` within tokio runtime.
+use std::net;
+use std::io;
+use std::future::{self, Future};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use tokio_crate::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_crate::io::unix::AsyncFd;
+
+use raw::{
+    libc,
+    Socket,
+    RecvMsg,
+    MsgFlags,
+    SocketError,
+    ShutdownType,
+    NON_BLOCKING,
+    NON_INHERITABLE
+};
+
+///Socket registered within tokio's reactor.
+///
+///Implements `AsyncRead` and `AsyncWrite`, which are meaningful only for stream sockets.
+pub struct AsyncSocket {
+    inner: AsyncFd<Socket>
+}
+
+impl AsyncSocket {
+    ///Registers socket within tokio's reactor.
+    ///
+    ///Socket is switched into non-blocking mode.
+    ///
+    ///Must be called within tokio runtime with I/O driver enabled.
+    pub fn new(socket: Socket) -> io::Result<AsyncSocket> {
+        socket.set_blocking(false)?;
+
+        //Socket owns its descriptor until it is dropped or taken back through `into_inner`
+        let inner = unsafe { AsyncFd::register(socket)? };
+
+        Ok(AsyncSocket {
+            inner
+        })
+    }
+
+    ///Returns reference to underlying socket.
+    pub fn get_ref(&self) -> &Socket {
+        self.inner.get_ref()
+    }
+
+    ///Deregisters socket from reactor and returns it.
+    ///
+    ///Note: socket remains in non-blocking mode.
+    pub fn into_inner(self) -> Socket {
+        self.inner.into_inner()
+    }
+
+    fn poll_read_io<T, F: FnMut(&Socket) -> Result<T, SocketError>>(&self, cx: &mut Context, mut op: F) -> Poll<io::Result<T>> {
+        loop {
+            let mut guard = match self.inner.poll_read_ready(cx) {
+                Poll::Ready(Ok(guard)) => guard,
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending
+            };
+
+            if let Ok(result) = guard.try_io(|inner| op(inner.get_ref()).map_err(io::Error::from)) {
+                return Poll::Ready(result);
+            }
+        }
+    }
+
+    fn poll_write_io<T, F: FnMut(&Socket) -> Result<T, SocketError>>(&self, cx: &mut Context, mut op: F) -> Poll<io::Result<T>> {
+        loop {
+            let mut guard = match self.inner.poll_write_ready(cx) {
+                Poll::Ready(Ok(guard)) => guard,
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => return Poll::Pending
+            };
+
+            if let Ok(result) = guard.try_io(|inner| op(inner.get_ref()).map_err(io::Error::from)) {
+                return Poll::Ready(result);
+            }
+        }
+    }
+
+    ///Connects socket with remote address.
+    ///
+    ///Completes once connection is established or failed.
+    pub fn connect<'a>(&'a self, addr: &'a net::SocketAddr) -> impl Future<Output = io::Result<()>> + 'a {
+        let mut in_progress = false;
+
+        future::poll_fn(move |cx| {
+            if !in_progress {
+                match self.get_ref().connect(addr) {
+                    Ok(()) => return Poll::Ready(Ok(())),
+                    Err(ref error) if error.raw_os_error() == Some(libc::EINPROGRESS) => in_progress = true,
+                    Err(error) => return Poll::Ready(Err(error.into()))
+                }
+            }
+
+            loop {
+                let mut guard = match self.inner.poll_write_ready(cx) {
+                    Poll::Ready(Ok(guard)) => guard,
+                    Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                    Poll::Pending => return Poll::Pending
+                };
+
+                match self.get_ref().take_error() {
+                    Ok(None) => (),
+                    Ok(Some(error)) => return Poll::Ready(Err(error.with_addr(*addr).into())),
+                    Err(error) => return Poll::Ready(Err(error.into()))
+                }
+
+                //Socket is registered before connect, so readiness might be stale.
+                //Connection is established only once peer is known.
+                match self.get_ref().peer_name() {
+                    Ok(_) => return Poll::Ready(Ok(())),
+                    Err(ref error) if error.kind() == io::ErrorKind::NotConnected => guard.clear_ready(),
+                    Err(error) => return Poll::Ready(Err(error.into()))
+                }
+            }
+        })
+    }
+
+    ///Attempts to accept new incoming connection.
+    ///
+    ///Accepted socket is non-blocking and not inheritable.
+    pub fn poll_accept(&self, cx: &mut Context) -> Poll<io::Result<(AsyncSocket, net::SocketAddr)>> {
+        match self.poll_read_io(cx, |socket| socket.accept4(NON_BLOCKING | NON_INHERITABLE)) {
+            Poll::Ready(Ok((socket, addr))) => Poll::Ready(AsyncSocket::new(socket).map(|socket| (socket, addr))),
+            Poll::Ready(Err(error)) => Poll::Ready(Err(error)),
+            Poll::Pending => Poll::Pending
+        }
+    }
+
+    ///Accepts new incoming connection.
+    ///
+    ///Accepted socket is non-blocking and not inheritable.
+    pub fn accept<'a>(&'a self) -> impl Future<Output = io::Result<(AsyncSocket, net::SocketAddr)>> + 'a {
+        future::poll_fn(move |cx| self.poll_accept(cx))
+    }
+
+    ///Attempts to receive some bytes from socket.
+    pub fn poll_recv(&self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.poll_read_io(cx, |socket| socket.recv(buf, MsgFlags::empty()))
+    }
+
+    ///Receives some bytes from socket.
+    ///
+    ///Number of received bytes is returned on success
+    pub fn recv<'a>(&'a self, buf: &'a mut [u8]) -> impl Future<Output = io::Result<usize>> + 'a {
+        future::poll_fn(move |cx| self.poll_recv(cx, buf))
+    }
+
+    ///Attempts to receive some bytes from socket along with remote address.
+    pub fn poll_recv_from(&self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<(usize, net::SocketAddr)>> {
+        self.poll_read_io(cx, |socket| socket.recv_from(buf, MsgFlags::empty()))
+    }
+
+    ///Receives some bytes from socket.
+    ///
+    ///Number of received bytes and remote address are returned on success.
+    pub fn recv_from<'a>(&'a self, buf: &'a mut [u8]) -> impl Future<Output = io::Result<(usize, net::SocketAddr)>> + 'a {
+        future::poll_fn(move |cx| self.poll_recv_from(cx, buf))
+    }
+
+    ///Attempts to receive message with its ancillary data.
+    pub fn poll_recv_msg(&self, cx: &mut Context, bufs: &mut [io::IoSliceMut], control: &mut [u8], flags: MsgFlags) -> Poll<io::Result<RecvMsg>> {
+        self.poll_read_io(cx, |socket| socket.recv_msg(bufs, control, flags))
+    }
+
+    ///Receives message with its ancillary data.
+    ///
+    ///See `Socket::recv_msg`
+    pub fn recv_msg<'a>(&'a self, bufs: &'a mut [io::IoSliceMut<'a>], control: &'a mut [u8], flags: MsgFlags) -> impl Future<Output = io::Result<RecvMsg>> + 'a {
+        future::poll_fn(move |cx| self.poll_recv_msg(cx, bufs, control, flags))
+    }
+
+    ///Attempts to send some bytes through socket.
+    pub fn poll_send(&self, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.poll_write_io(cx, |socket| socket.send(buf, MsgFlags::empty()))
+    }
+
+    ///Sends some bytes through socket.
+    ///
+    ///Number of sent bytes is returned.
+    pub fn send<'a>(&'a self, buf: &'a [u8]) -> impl Future<Output = io::Result<usize>> + 'a {
+        future::poll_fn(move |cx| self.poll_send(cx, buf))
+    }
+
+    ///Attempts to send some bytes toward specified peer.
+    pub fn poll_send_to(&self, cx: &mut Context, buf: &[u8], peer_addr: &net::SocketAddr) -> Poll<io::Result<usize>> {
+        self.poll_write_io(cx, |socket| socket.send_to(buf, peer_addr, MsgFlags::empty()))
+    }
+
+    ///Sends some bytes through socket toward specified peer.
+    ///
+    ///Number of sent bytes is returned.
+    pub fn send_to<'a>(&'a self, buf: &'a [u8], peer_addr: &'a net::SocketAddr) -> impl Future<Output = io::Result<usize>> + 'a {
+        future::poll_fn(move |cx| self.poll_send_to(cx, buf, peer_addr))
+    }
+}
+
+impl AsRawFd for AsyncSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl AsyncRead for AsyncSocket {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<io::Result<()>> {
+        match self.poll_recv(cx, buf.initialize_unfilled()) {
+            Poll::Ready(Ok(len)) => {
+                buf.advance(len);
+                Poll::Ready(Ok(()))
+            },
+            Poll::Ready(Err(error)) => Poll::Ready(Err(error)),
+            Poll::Pending => Poll::Pending
+        }
+    }
+}
+
+impl AsyncWrite for AsyncSocket {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.poll_send(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(self.inner.get_ref().shutdown(ShutdownType::Send).map_err(io::Error::from))
+    }
+}
@@ -0,0 +1,665 @@
+//! io_uring backend.
+//!
+//! Provides `Ring` which submits socket operations to kernel in batches and harvests their completions.
+//!
+//! Buffers are owned by ring while operation is in flight and handed back within `Completion`.
+//!
+//! Sockets must outlive operations that are submitted on them.
+use std::io;
+use std::cmp;
+use std::mem;
+use std::net;
+use std::alloc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+
+use io_uring::{opcode, cqueue, squeue, types, IoUring};
+
+use raw::{
+    libc,
+    Socket,
+    AcceptFlags,
+    MsgFlags,
+    NOSIGNAL,
+    get_raw_addr,
+    sockaddr_to_addr
+};
+
+//Reserved for operations whose completions are of no interest e.g. cancellation.
+const IGNORED: u64 = u64::MAX;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+///Identifies submitted operation.
+pub struct Token(u64);
+
+struct Addr {
+    storage: libc::sockaddr_storage,
+    len: libc::socklen_t
+}
+
+impl Addr {
+    fn empty() -> Box<Addr> {
+        Box::new(Addr {
+            storage: unsafe { mem::zeroed() },
+            len: mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t
+        })
+    }
+
+    fn new(addr: &net::SocketAddr) -> Box<Addr> {
+        let (storage, len) = get_raw_addr(addr);
+
+        Box::new(Addr {
+            storage,
+            len
+        })
+    }
+
+    fn to_addr(&self) -> Option<net::SocketAddr> {
+        match self.len {
+            0 => None,
+            len => sockaddr_to_addr(&self.storage, len).ok()
+        }
+    }
+}
+
+struct Msg {
+    hdr: libc::msghdr,
+    iov: libc::iovec,
+    addr: Addr,
+    buf: Vec<u8>,
+    control: Vec<u8>
+}
+
+impl Msg {
+    fn new(buf: Vec<u8>, control: Vec<u8>, addr: Option<&net::SocketAddr>) -> Box<Msg> {
+        let mut msg = Box::new(Msg {
+            hdr: unsafe { mem::zeroed() },
+            iov: libc::iovec { iov_base: std::ptr::null_mut(), iov_len: 0 },
+            addr: match addr {
+                Some(addr) => *Addr::new(addr),
+                None => *Addr::empty()
+            },
+            buf,
+            control
+        });
+
+        //Box is never moved while operation is in flight so these pointers remain valid.
+        msg.iov.iov_base = msg.buf.as_mut_ptr() as *mut libc::c_void;
+        msg.iov.iov_len = msg.buf.len();
+        msg.hdr.msg_iov = &mut msg.iov;
+        msg.hdr.msg_iovlen = 1;
+        msg.hdr.msg_name = &mut msg.addr.storage as *mut _ as *mut libc::c_void;
+        msg.hdr.msg_namelen = match addr {
+            Some(_) => msg.addr.len,
+            None => 0
+        };
+        if !msg.control.is_empty() {
+            msg.hdr.msg_control = msg.control.as_mut_ptr() as *mut libc::c_void;
+            msg.hdr.msg_controllen = msg.control.len() as _;
+        }
+
+        msg
+    }
+
+    fn for_recv(buf: Vec<u8>, control: Vec<u8>) -> Box<Msg> {
+        let mut msg = Msg::new(buf, control, None);
+        msg.hdr.msg_namelen = msg.addr.len;
+        msg
+    }
+}
+
+enum Op {
+    Accept(Box<Addr>),
+    AcceptMulti,
+    //Address is read by kernel once operation gets executed.
+    Connect(#[allow(dead_code)] Box<Addr>),
+    Send(Vec<u8>),
+    Recv(Vec<u8>),
+    RecvMulti(u16, u64),
+    SendMsg(Box<Msg>),
+    RecvMsg(Box<Msg>)
+}
+
+///Buffer selected by kernel from buffer ring.
+///
+///Its content can be accessed through `Ring::provided`.
+///
+///Buffer must be given back through `Ring::recycle`, otherwise it is lost for the group.
+#[must_use]
+#[derive(Debug)]
+pub struct ProvidedBuf {
+    group: u16,
+    //Registration of group, buffer is picked from.
+    generation: u64,
+    id: u16,
+    len: usize
+}
+
+impl ProvidedBuf {
+    ///Returns group of buffer ring.
+    pub fn group(&self) -> u16 {
+        self.group
+    }
+
+    ///Returns number of received bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    ///Returns whether buffer holds no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+fn page_size() -> usize {
+    match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as usize,
+        _ => 4096
+    }
+}
+
+///Buffer ring registered within kernel.
+struct BufRing {
+    ring: *mut types::BufRingEntry,
+    layout: alloc::Layout,
+    generation: u64,
+    entries: u16,
+    tail: u16,
+    buf_len: usize,
+    bufs: Vec<u8>
+}
+
+impl BufRing {
+    fn new(entries: u16, buf_len: usize, generation: u64) -> io::Result<BufRing> {
+        if !entries.is_power_of_two() || entries > 32768 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Number of entries must be power of two, not exceeding 32768"));
+        }
+
+        if buf_len == 0 || buf_len > u32::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Length of buffer must be non-zero, not exceeding u32::MAX"));
+        }
+
+        let size = entries as usize * mem::size_of::<types::BufRingEntry>();
+        let layout = match alloc::Layout::from_size_align(size, page_size()) {
+            Ok(layout) => layout,
+            Err(_) => return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid buffer ring layout"))
+        };
+        let bufs_len = match (entries as usize).checked_mul(buf_len) {
+            Some(len) => len,
+            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "Total size of buffers overflows"))
+        };
+
+        let ring = unsafe { alloc::alloc_zeroed(layout) } as *mut types::BufRingEntry;
+        if ring.is_null() {
+            return Err(io::Error::from(io::ErrorKind::OutOfMemory));
+        }
+
+        let mut buf_ring = BufRing {
+            ring,
+            layout,
+            generation,
+            entries,
+            tail: 0,
+            buf_len,
+            bufs: vec![0; bufs_len]
+        };
+
+        for id in 0..entries {
+            buf_ring.push(id);
+        }
+        buf_ring.publish();
+
+        Ok(buf_ring)
+    }
+
+    ///Returns buffer of `id`, if it belongs to this ring.
+    fn buf(&self, id: u16) -> Option<&[u8]> {
+        if id >= self.entries {
+            return None;
+        }
+
+        let start = id as usize * self.buf_len;
+        Some(&self.bufs[start..start + self.buf_len])
+    }
+
+    fn push(&mut self, id: u16) {
+        let addr = self.bufs[id as usize * self.buf_len..].as_ptr() as u64;
+        let entry = unsafe { &mut *self.ring.add((self.tail & (self.entries - 1)) as usize) };
+        entry.set_addr(addr);
+        entry.set_len(self.buf_len as u32);
+        entry.set_bid(id);
+        self.tail = self.tail.wrapping_add(1);
+    }
+
+    ///Makes pushed buffers visible to kernel.
+    fn publish(&self) {
+        unsafe {
+            let tail = types::BufRingEntry::tail(self.ring) as *const AtomicU16;
+            (*tail).store(self.tail, Ordering::Release);
+        }
+    }
+}
+
+impl Drop for BufRing {
+    fn drop(&mut self) {
+        unsafe {
+            alloc::dealloc(self.ring as *mut u8, self.layout);
+        }
+    }
+}
+
+///Completed operation.
+pub enum Completion {
+    ///Outcome of `Ring::accept` and `Ring::accept_multi`
+    ///
+    ///Remote address is available only for single shot accept.
+    Accept {
+        token: Token,
+        result: io::Result<(Socket, Option<net::SocketAddr>)>,
+        more: bool
+    },
+    ///Outcome of `Ring::connect`
+    Connect {
+        token: Token,
+        result: io::Result<()>
+    },
+    ///Outcome of `Ring::send`, along with submitted buffer.
+    Send {
+        token: Token,
+        result: io::Result<usize>,
+        buf: Vec<u8>
+    },
+    ///Outcome of `Ring::recv`, along with submitted buffer.
+    Recv {
+        token: Token,
+        result: io::Result<usize>,
+        buf: Vec<u8>
+    },
+    ///Outcome of `Ring::recv_multi`
+    ///
+    ///No buffer is provided once peer closes connection.
+    RecvProvided {
+        token: Token,
+        result: io::Result<Option<ProvidedBuf>>,
+        more: bool
+    },
+    ///Outcome of `Ring::send_msg`, along with submitted buffers.
+    SendMsg {
+        token: Token,
+        result: io::Result<usize>,
+        buf: Vec<u8>,
+        control: Vec<u8>
+    },
+    ///Outcome of `Ring::recv_msg`, along with submitted buffers.
+    RecvMsg {
+        token: Token,
+        result: io::Result<usize>,
+        buf: Vec<u8>,
+        control: Vec<u8>,
+        ///Number of bytes written into control buffer.
+        control_len: usize,
+        addr: Option<net::SocketAddr>,
+        ///Flags set by `recvmsg()` e.g. `TRUNC`, `CTRUNC` or `EOR`.
+        flags: MsgFlags
+    }
+}
+
+impl Completion {
+    ///Returns token of completed operation.
+    pub fn token(&self) -> Token {
+        match *self {
+            Completion::Accept { token, .. } => token,
+            Completion::Connect { token, .. } => token,
+            Completion::Send { token, .. } => token,
+            Completion::Recv { token, .. } => token,
+            Completion::RecvProvided { token, .. } => token,
+            Completion::SendMsg { token, .. } => token,
+            Completion::RecvMsg { token, .. } => token
+        }
+    }
+}
+
+fn to_result(result: i32) -> io::Result<usize> {
+    match result {
+        result if result < 0 => Err(io::Error::from_raw_os_error(-result)),
+        result => Ok(result as usize)
+    }
+}
+
+///Submission based socket I/O.
+pub struct Ring {
+    ring: IoUring,
+    ops: HashMap<u64, Op>,
+    buf_rings: HashMap<u16, BufRing>,
+    next_generation: u64,
+    next_token: u64
+}
+
+impl Ring {
+    ///Creates new ring with specified number of submission queue entries.
+    pub fn new(entries: u32) -> io::Result<Ring> {
+        Ok(Ring {
+            ring: IoUring::new(entries)?,
+            ops: HashMap::new(),
+            buf_rings: HashMap::new(),
+            next_generation: 0,
+            next_token: 0
+        })
+    }
+
+    ///Returns number of operations that are in flight.
+    pub fn in_flight(&self) -> usize {
+        self.ops.len()
+    }
+
+    fn push(&mut self, entry: squeue::Entry, op: Option<Op>) -> io::Result<Token> {
+        let token = match op {
+            Some(_) => {
+                let token = self.next_token;
+                self.next_token = (self.next_token + 1) % IGNORED;
+                token
+            },
+            None => IGNORED
+        };
+        let entry = entry.user_data(token);
+
+        //Submission queue is flushed into kernel once it gets full.
+        while unsafe { self.ring.submission().push(&entry) }.is_err() {
+            self.ring.submit()?;
+        }
+
+        if let Some(op) = op {
+            self.ops.insert(token, op);
+        }
+
+        Ok(Token(token))
+    }
+
+    ///Registers buffer ring of `entries` buffers, each of `buf_len` bytes, under specified group.
+    ///
+    ///Number of entries must be power of two.
+    ///
+    ///Requires Linux 5.19.
+    pub fn register_buf_ring(&mut self, group: u16, entries: u16, buf_len: usize) -> io::Result<()> {
+        if self.buf_rings.contains_key(&group) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "Buffer group is already registered"));
+        }
+
+        let buf_ring = BufRing::new(entries, buf_len, self.next_generation)?;
+
+        unsafe {
+            self.ring.submitter().register_buf_ring_with_flags(buf_ring.ring as u64, entries, group, 0)?;
+        }
+
+        self.buf_rings.insert(group, buf_ring);
+        self.next_generation += 1;
+        Ok(())
+    }
+
+    ///Unregisters buffer ring of specified group.
+    pub fn unregister_buf_ring(&mut self, group: u16) -> io::Result<()> {
+        self.ring.submitter().unregister_buf_ring(group)?;
+        self.buf_rings.remove(&group);
+        Ok(())
+    }
+
+    fn buf_ring_of(&self, buf: &ProvidedBuf) -> Option<&BufRing> {
+        self.buf_rings.get(&buf.group).filter(|buf_ring| buf_ring.generation == buf.generation)
+    }
+
+    ///Returns received bytes of provided buffer.
+    ///
+    ///Returns `None` if buffer's group is unregistered, even if it is registered again since.
+    pub fn provided(&self, buf: &ProvidedBuf) -> Option<&[u8]> {
+        self.buf_ring_of(buf).and_then(|buf_ring| buf_ring.buf(buf.id)).and_then(|bytes| bytes.get(..buf.len))
+    }
+
+    ///Gives provided buffer back to its ring, so that kernel can use it again.
+    ///
+    ///Buffer of unregistered group is dropped.
+    pub fn recycle(&mut self, buf: ProvidedBuf) {
+        if let Some(buf_ring) = self.buf_rings.get_mut(&buf.group) {
+            if buf_ring.generation == buf.generation && buf.id < buf_ring.entries {
+                buf_ring.push(buf.id);
+                buf_ring.publish();
+            }
+        }
+    }
+
+    ///Submits accept of single connection.
+    pub fn accept(&mut self, socket: &Socket, flags: AcceptFlags) -> io::Result<Token> {
+        let mut addr = Addr::empty();
+        let entry = opcode::Accept::new(types::Fd(socket.as_raw_fd()), &mut addr.storage as *mut _ as *mut _, &mut addr.len).flags(flags.bits()).build();
+
+        self.push(entry, Some(Op::Accept(addr)))
+    }
+
+    ///Submits multishot accept, which produces completion for each incoming connection.
+    ///
+    ///Requires Linux 5.19.
+    pub fn accept_multi(&mut self, socket: &Socket, flags: AcceptFlags) -> io::Result<Token> {
+        let entry = opcode::AcceptMulti::new(types::Fd(socket.as_raw_fd())).flags(flags.bits()).build();
+
+        self.push(entry, Some(Op::AcceptMulti))
+    }
+
+    ///Submits connect to remote address.
+    pub fn connect(&mut self, socket: &Socket, addr: &net::SocketAddr) -> io::Result<Token> {
+        let addr = Addr::new(addr);
+        let entry = opcode::Connect::new(types::Fd(socket.as_raw_fd()), &addr.storage as *const _ as *const _, addr.len).build();
+
+        self.push(entry, Some(Op::Connect(addr)))
+    }
+
+    ///Submits send of buffer.
+    ///
+    ///Send may complete partially, in which case completion reports number of bytes sent
+    ///and caller must submit the rest. At most `u32::MAX` bytes are sent at once.
+    ///
+    ///`NOSIGNAL` is always added to `flags`, so closed connection results in `BrokenPipe` instead of `SIGPIPE`.
+    pub fn send(&mut self, socket: &Socket, buf: Vec<u8>, flags: MsgFlags) -> io::Result<Token> {
+        let len = cmp::min(buf.len(), u32::MAX as usize) as u32;
+        let entry = opcode::Send::new(types::Fd(socket.as_raw_fd()), buf.as_ptr(), len).flags((flags | NOSIGNAL).bits()).build();
+
+        self.push(entry, Some(Op::Send(buf)))
+    }
+
+    ///Submits receive into buffer.
+    ///
+    ///Up to `buf.len()` bytes are received.
+    pub fn recv(&mut self, socket: &Socket, mut buf: Vec<u8>, flags: MsgFlags) -> io::Result<Token> {
+        let len = cmp::min(buf.len(), u32::MAX as usize) as u32;
+        let entry = opcode::Recv::new(types::Fd(socket.as_raw_fd()), buf.as_mut_ptr(), len).flags(flags.bits()).build();
+
+        self.push(entry, Some(Op::Recv(buf)))
+    }
+
+    ///Submits multishot receive, which picks buffers from ring of specified group.
+    ///
+    ///Requires Linux 6.0.
+    pub fn recv_multi(&mut self, socket: &Socket, group: u16, flags: MsgFlags) -> io::Result<Token> {
+        let generation = match self.buf_rings.get(&group) {
+            Some(buf_ring) => buf_ring.generation,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Buffer group is not registered"))
+        };
+
+        let entry = opcode::RecvMulti::new(types::Fd(socket.as_raw_fd()), group).flags(flags.bits()).build();
+
+        self.push(entry, Some(Op::RecvMulti(group, generation)))
+    }
+
+    ///Submits send of message, with optional destination and ancillary data.
+    ///
+    ///`NOSIGNAL` is always added to `flags`, same as with `send`.
+    pub fn send_msg(&mut self, socket: &Socket, buf: Vec<u8>, control: Vec<u8>, addr: Option<&net::SocketAddr>, flags: MsgFlags) -> io::Result<Token> {
+        let msg = Msg::new(buf, control, addr);
+        let entry = opcode::SendMsg::new(types::Fd(socket.as_raw_fd()), &msg.hdr).flags((flags | NOSIGNAL).bits() as u32).build();
+
+        self.push(entry, Some(Op::SendMsg(msg)))
+    }
+
+    ///Submits receive of message along with its ancillary data.
+    ///
+    ///Control messages are written into `control`, which may be empty if they are of no interest.
+    ///Received descriptors are not inheritable, as `MSG_CMSG_CLOEXEC` is always added to `flags`.
+    pub fn recv_msg(&mut self, socket: &Socket, buf: Vec<u8>, control: Vec<u8>, flags: MsgFlags) -> io::Result<Token> {
+        let mut msg = Msg::for_recv(buf, control);
+        let flags = flags.bits() | libc::MSG_CMSG_CLOEXEC;
+        let entry = opcode::RecvMsg::new(types::Fd(socket.as_raw_fd()), &mut msg.hdr).flags(flags as u32).build();
+
+        self.push(entry, Some(Op::RecvMsg(msg)))
+    }
+
+    ///Requests cancellation of operation.
+    ///
+    ///Cancelled operation completes with `ECANCELED`.
+    pub fn cancel(&mut self, token: Token) -> io::Result<()> {
+        let entry = opcode::AsyncCancel::new(token.0).build();
+
+        self.push(entry, None).map(|_| ())
+    }
+
+    ///Submits queued operations to kernel.
+    ///
+    ///Returns number of submitted operations.
+    pub fn submit(&mut self) -> io::Result<usize> {
+        self.ring.submit()
+    }
+
+    ///Submits queued operations to kernel and waits for at least `want` completions.
+    pub fn submit_and_wait(&mut self, want: usize) -> io::Result<usize> {
+        self.ring.submit_and_wait(want)
+    }
+
+    ///Harvests available completions, without waiting.
+    pub fn completions(&mut self) -> Vec<Completion> {
+        let mut result = Vec::new();
+        let entries: Vec<cqueue::Entry> = self.ring.completion().collect();
+
+        for entry in entries {
+            if let Some(completion) = self.complete(entry) {
+                result.push(completion);
+            }
+        }
+
+        result
+    }
+
+    fn complete(&mut self, entry: cqueue::Entry) -> Option<Completion> {
+        let token = Token(entry.user_data());
+        let more = cqueue::more(entry.flags());
+
+        //Multishot operations remain in flight until kernel reports otherwise.
+        let op = if more {
+            match self.ops.get(&token.0) {
+                Some(&Op::AcceptMulti) => Op::AcceptMulti,
+                Some(&Op::RecvMulti(group, generation)) => Op::RecvMulti(group, generation),
+                _ => return None
+            }
+        } else {
+            self.ops.remove(&token.0)?
+        };
+
+        let completion = match op {
+            Op::Accept(addr) => Completion::Accept {
+                token,
+                result: to_result(entry.result()).map(|fd| (unsafe { Socket::from_raw_fd(fd as libc::c_int) }, addr.to_addr())),
+                more
+            },
+            Op::AcceptMulti => Completion::Accept {
+                token,
+                result: to_result(entry.result()).map(|fd| (unsafe { Socket::from_raw_fd(fd as libc::c_int) }, None)),
+                more
+            },
+            Op::Connect(_) => Completion::Connect {
+                token,
+                result: to_result(entry.result()).map(|_| ())
+            },
+            Op::Send(buf) => Completion::Send {
+                token,
+                result: to_result(entry.result()),
+                buf
+            },
+            Op::Recv(buf) => Completion::Recv {
+                token,
+                result: to_result(entry.result()),
+                buf
+            },
+            Op::RecvMulti(group, generation) => {
+                let result = to_result(entry.result()).and_then(|len| match cqueue::buffer_select(entry.flags()) {
+                    Some(id) => Ok(Some(ProvidedBuf {
+                        group,
+                        generation,
+                        id,
+                        len
+                    })),
+                    //Kernel doesn't pick buffer when peer closes connection
+                    None if len == 0 => Ok(None),
+                    None => Err(io::Error::other("Kernel didn't select buffer"))
+                });
+
+                Completion::RecvProvided {
+                    token,
+                    result,
+                    more
+                }
+            },
+            Op::SendMsg(msg) => {
+                let msg = *msg;
+
+                Completion::SendMsg {
+                    token,
+                    result: to_result(entry.result()),
+                    buf: msg.buf,
+                    control: msg.control
+                }
+            },
+            Op::RecvMsg(msg) => {
+                let msg = *msg;
+                let addr = Addr {
+                    storage: msg.addr.storage,
+                    len: msg.hdr.msg_namelen
+                };
+
+                Completion::RecvMsg {
+                    token,
+                    result: to_result(entry.result()),
+                    control_len: msg.hdr.msg_controllen as _,
+                    addr: addr.to_addr(),
+                    flags: MsgFlags::from_bits_truncate(msg.hdr.msg_flags),
+                    buf: msg.buf,
+                    control: msg.control
+                }
+            }
+        };
+
+        Some(completion)
+    }
+}
+
+impl Drop for Ring {
+    fn drop(&mut self) {
+        //Kernel may still write into buffers of pending operations so they need to be reaped first.
+        let tokens: Vec<u64> = self.ops.keys().cloned().collect();
+        for token in tokens {
+            let _ = self.cancel(Token(token));
+        }
+
+        while !self.ops.is_empty() {
+            if self.ring.submit_and_wait(1).is_err() {
+                //Leaking is the only safe option left.
+                mem::forget(mem::take(&mut self.ops));
+                mem::forget(mem::take(&mut self.buf_rings));
+                return;
+            }
+
+            self.completions();
+        }
+
+        for group in self.buf_rings.keys() {
+            let _ = self.ring.submitter().unregister_buf_ring(*group);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+#![cfg(all(unix, feature = "future"))]
+extern crate lazy_socket;
+#[cfg(target_os = "linux")]
+extern crate libc;
+
+use std::io;
+use std::net;
+use std::thread;
+use std::time;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Wake};
+
+use lazy_socket::raw::*;
+use lazy_socket::future::AsyncSocket;
+
+struct ThreadWaker(thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match Pin::as_mut(&mut future).poll(&mut cx) {
+            Poll::Ready(result) => return result,
+            Poll::Pending => thread::park()
+        }
+    }
+}
+
+fn tcp_listener() -> (Socket, net::SocketAddr) {
+    let socket = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(socket.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(socket.listen(1).is_ok());
+    let addr = socket.name().unwrap();
+
+    (socket, addr)
+}
+
+#[test]
+fn future_accept_recv() {
+    let data = [1, 2, 3, 4];
+    let (server, server_addr) = tcp_listener();
+    let server = AsyncSocket::new(server).unwrap();
+
+    let th = thread::spawn(move || {
+        //Give chance for accept to get registered within reactor.
+        thread::sleep(time::Duration::from_millis(50));
+        let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+        assert!(client.connect(&server_addr).is_ok());
+        thread::sleep(time::Duration::from_millis(50));
+        assert_eq!(client.send(&data, MsgFlags::empty()).unwrap(), data.len());
+        client.name().unwrap()
+    });
+
+    let (result_socket, result_addr) = block_on(server.accept()).unwrap();
+    assert!(!result_socket.get_ref().get_inheritable().unwrap());
+
+    let mut buf = [0; 10];
+    let result_len = block_on(result_socket.recv(&mut buf)).unwrap();
+    assert_eq!(result_len, data.len());
+    assert_eq!(&buf[..result_len], data);
+
+    assert_eq!(th.join().unwrap(), result_addr);
+}
+
+#[test]
+fn future_connect_send() {
+    let data = [1, 2, 3, 4];
+    let (server, server_addr) = tcp_listener();
+
+    let th = thread::spawn(move || {
+        let (socket, _) = server.accept().unwrap();
+        let mut buf = [0; 10];
+        let result_len = socket.recv(&mut buf, MsgFlags::empty()).unwrap();
+        assert_eq!(&buf[..result_len], data);
+    });
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    let client = AsyncSocket::new(client).unwrap();
+    assert!(block_on(client.connect(&server_addr)).is_ok());
+    assert_eq!(block_on(client.send(&data)).unwrap(), data.len());
+
+    assert!(th.join().is_ok());
+}
+
+#[test]
+fn future_connect_refused() {
+    //Reserve port and make sure nobody listens on it.
+    let addr = {
+        let (server, _) = tcp_listener();
+        server.name().unwrap()
+    };
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    let client = AsyncSocket::new(client).unwrap();
+    let result = block_on(client.connect(&addr));
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::ConnectionRefused);
+}
+
+#[test]
+fn future_into_inner() {
+    let (server, _) = tcp_listener();
+    let raw = server.raw();
+    let server = AsyncSocket::new(server).unwrap();
+
+    let server = server.into_inner();
+    assert_eq!(server.raw(), raw);
+    let result = server.accept();
+    assert!(result.is_err() && result.err().unwrap().kind() == io::ErrorKind::WouldBlock);
+}
+
+#[test]
+fn future_drop_removes_waker() {
+    struct CountWaker(AtomicUsize);
+
+    impl Wake for CountWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (server, server_addr) = tcp_listener();
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(client.connect(&server_addr).is_ok());
+    let (accepted, _) = server.accept().unwrap();
+    let accepted = AsyncSocket::new(accepted).unwrap();
+
+    let count = Arc::new(CountWaker(AtomicUsize::new(0)));
+    let waker = count.clone().into();
+    let mut cx = Context::from_waker(&waker);
+
+    let mut buf = [0; 10];
+    {
+        let mut recv = accepted.recv(&mut buf);
+        assert!(Pin::new(&mut recv).poll(&mut cx).is_pending());
+    }
+
+    //Abandoned future must not be woken once socket becomes ready.
+    assert_eq!(client.send(&[1], MsgFlags::empty()).unwrap(), 1);
+    thread::sleep(time::Duration::from_millis(100));
+    assert_eq!(count.0.load(Ordering::SeqCst), 0);
+
+    assert_eq!(block_on(accepted.recv(&mut buf)).unwrap(), 1);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn future_reactor_pipe_cloexec() {
+    //Start reactor by waiting on socket.
+    let (server, _) = tcp_listener();
+    let server = AsyncSocket::new(server).unwrap();
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut accept = Box::pin(server.accept());
+    assert!(accept.as_mut().poll(&mut Context::from_waker(&waker)).is_pending());
+
+    let mut pipes = 0;
+    for entry in std::fs::read_dir("/proc/self/fd").unwrap() {
+        let entry = entry.unwrap();
+        let fd: i32 = entry.file_name().to_string_lossy().parse().unwrap();
+        let is_pipe = std::fs::read_link(entry.path()).map(|path| path.to_string_lossy().starts_with("pipe:")).unwrap_or(false);
+
+        //Standard streams might be pipes of test harness.
+        if is_pipe && fd > 2 {
+            let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+            assert_ne!(flags & libc::FD_CLOEXEC, 0, "Pipe {} is inheritable", fd);
+            pipes += 1;
+        }
+    }
+
+    assert!(pipes >= 2);
+}
@@ -0,0 +1,125 @@
+//!Backoff of `Incoming` on exhausted descriptors.
+//!
+//!Exhaustion affects whole process, so tests are run one after another without libtest harness.
+extern crate lazy_socket;
+#[cfg(unix)]
+extern crate libc;
+
+#[cfg(unix)]
+use std::net;
+#[cfg(unix)]
+use std::thread;
+#[cfg(unix)]
+use std::time;
+#[cfg(unix)]
+use std::io::Read;
+#[cfg(unix)]
+use std::str::FromStr;
+#[cfg(unix)]
+use std::os::raw::c_int;
+#[cfg(unix)]
+use lazy_socket::raw::*;
+
+#[cfg(unix)]
+fn tcp_listener() -> (Socket, net::SocketAddr) {
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(4).is_ok());
+    let server_addr = server.name().unwrap();
+
+    (server, server_addr)
+}
+
+#[cfg(unix)]
+fn exhaust_descriptors() -> Vec<c_int> {
+    let mut fillers = Vec::new();
+    loop {
+        match unsafe { libc::dup(0) } {
+            -1 => return fillers,
+            fd => fillers.push(fd)
+        }
+    }
+}
+
+#[cfg(unix)]
+fn release_descriptors(fillers: Vec<c_int>) {
+    for fd in fillers {
+        unsafe { libc::close(fd) };
+    }
+}
+
+#[cfg(unix)]
+fn is_closed(client: &net::TcpStream) -> bool {
+    let mut buf = [0; 1];
+    match (&*client).read(&mut buf) {
+        Ok(0) => true,
+        Err(ref error) => error.kind() == std::io::ErrorKind::ConnectionReset,
+        Ok(_) => false
+    }
+}
+
+#[cfg(unix)]
+fn incoming_backoff() {
+    let (server, server_addr) = tcp_listener();
+    assert!(server.set_blocking(false).is_ok());
+
+    let client = net::TcpStream::connect(server_addr).unwrap();
+    let mut incoming = server.incoming(AcceptFlags::empty()).with_backoff(time::Duration::from_millis(1)).unwrap();
+
+    let fillers = exhaust_descriptors();
+
+    //Pending connection is shed, after which there is nothing to accept.
+    let result = incoming.next();
+
+    release_descriptors(fillers);
+
+    match result {
+        Some(Err(ref error)) => assert_eq!(error.kind(), std::io::ErrorKind::WouldBlock),
+        _ => panic!("Expected WouldBlock after shedding")
+    }
+    assert!(is_closed(&client));
+}
+
+#[cfg(unix)]
+fn incoming_backoff_blocking() {
+    let (server, server_addr) = tcp_listener();
+
+    let shed_client = net::TcpStream::connect(server_addr).unwrap();
+    let mut incoming = server.incoming(AcceptFlags::empty()).with_backoff(time::Duration::from_millis(1)).unwrap();
+
+    let fillers = exhaust_descriptors();
+
+    //Once there is nothing left to shed, descriptors are released and next client arrives.
+    let client = thread::spawn(move || {
+        thread::sleep(time::Duration::from_millis(100));
+        release_descriptors(fillers);
+        net::TcpStream::connect(server_addr).unwrap()
+    });
+
+    //Shedding must neither block nor take next client.
+    let accepted = match incoming.next() {
+        Some(Ok((_, addr))) => addr,
+        _ => panic!("Expected next client to be accepted")
+    };
+    let client = client.join().unwrap();
+
+    assert_eq!(accepted, client.local_addr().unwrap());
+    assert!(is_closed(&shed_client));
+}
+
+#[cfg(unix)]
+fn main() {
+    let tests: &[(&str, fn())] = &[
+        ("incoming_backoff", incoming_backoff),
+        ("incoming_backoff_blocking", incoming_backoff_blocking)
+    ];
+
+    for &(name, test) in tests {
+        test();
+        println!("test {} ... ok", name);
+    }
+}
+
+#[cfg(not(unix))]
+fn main() {
+}
@@ -0,0 +1,73 @@
+#![cfg(all(unix, feature = "mio"))]
+extern crate lazy_socket;
+extern crate mio;
+
+use std::net;
+use std::time;
+use std::str::FromStr;
+
+use lazy_socket::raw::*;
+
+use mio::{Events, Interest, Poll, Token};
+
+const SERVER: Token = Token(0);
+const CLIENT: Token = Token(1);
+
+#[test]
+fn mio_register_listener() {
+    let mut poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    let timeout = Some(time::Duration::from_millis(1000));
+
+    let mut server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(1).is_ok());
+    assert!(server.set_blocking(false).is_ok());
+    let server_addr = server.name().unwrap();
+
+    assert!(poll.registry().register(&mut server, SERVER, Interest::READABLE).is_ok());
+
+    let mut client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(client.set_blocking(false).is_ok());
+    let _ = client.connect(&server_addr);
+    assert!(poll.registry().register(&mut client, CLIENT, Interest::WRITABLE).is_ok());
+
+    let mut server_ready = false;
+    let mut client_ready = false;
+    while !(server_ready && client_ready) {
+        assert!(poll.poll(&mut events, timeout).is_ok());
+        assert!(!events.is_empty());
+
+        for event in events.iter() {
+            match event.token() {
+                SERVER => {
+                    assert!(event.is_readable());
+                    server_ready = true;
+                },
+                CLIENT => {
+                    assert!(event.is_writable());
+                    client_ready = true;
+                },
+                _ => unreachable!()
+            }
+        }
+    }
+
+    let (mut accepted, _) = server.accept().unwrap();
+    assert!(poll.registry().deregister(&mut server).is_ok());
+
+    //Reuse token of deregistered socket and switch client to read interest.
+    assert!(poll.registry().register(&mut accepted, SERVER, Interest::WRITABLE).is_ok());
+    assert!(poll.registry().reregister(&mut client, CLIENT, Interest::READABLE).is_ok());
+    assert_eq!(accepted.send(&[1, 2, 3, 4], MsgFlags::empty()).unwrap(), 4);
+
+    let mut client_readable = false;
+    while !client_readable {
+        assert!(poll.poll(&mut events, timeout).is_ok());
+        assert!(!events.is_empty());
+        client_readable = events.iter().any(|event| event.token() == CLIENT && event.is_readable());
+    }
+
+    let mut buf = [0; 10];
+    assert_eq!(client.recv(&mut buf, MsgFlags::empty()).unwrap(), 4);
+}
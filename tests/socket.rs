@@ -14,7 +14,7 @@ fn socket_new_raw_icmp() {
     //Test requires admin privileges.
     let addr = net::SocketAddr::from_str("0.0.0.0:0").unwrap();
 
-    let socket = Socket::new(Family::IPv4, Type::RAW, Protocol::ICMPv4);
+    let socket = Socket::new(Domain::IPv4, SockType::RAW, Protocol::ICMPv4);
 
     if let Err(error) = socket {
         let error_code = error.raw_os_error().unwrap();
@@ -45,8 +45,8 @@ fn socket_new_raw_icmp() {
 
 #[test]
 fn socket_test_udp() {
-    let family = Family::IPv4;
-    let ty = Type::DATAGRAM;
+    let family = Domain::IPv4;
+    let ty = SockType::DATAGRAM;
     let proto = Protocol::UDP;
     let data = [1, 2, 3, 4];
     let addr = net::SocketAddr::from_str("127.0.0.1:1666").unwrap();
@@ -60,7 +60,7 @@ fn socket_test_udp() {
     assert!(client.bind(&net::SocketAddr::from_str("127.0.0.1:5666").unwrap()).is_ok());
     let client_addr = client.name().unwrap();
 
-    let result = client.send_to(&data, &addr, 0);
+    let result = client.send_to(&data, &addr, MsgFlags::empty());
     assert!(result.is_ok());
     let result = result.unwrap();
     assert_eq!(result, data.len());
@@ -68,7 +68,7 @@ fn socket_test_udp() {
     let mut read_data = [0; 10];
 
     // recv_from
-    let result = server.recv_from(&mut read_data, 0);
+    let result = server.recv_from(&mut read_data, MsgFlags::empty());
     assert!(result.is_ok());
     let (result_len, result_addr) = result.unwrap();
 
@@ -78,24 +78,24 @@ fn socket_test_udp() {
     assert_eq!(&read_data[..result_len], data);
 
     // 2 send + 2 recv
-    let result = client.send_to(&data, &addr, 0);
+    let result = client.send_to(&data, &addr, MsgFlags::empty());
     assert!(result.is_ok());
     let result = result.unwrap();
     assert_eq!(result, data.len());
 
-    let result = client.send_to(&data, &addr, 0);
+    let result = client.send_to(&data, &addr, MsgFlags::empty());
     assert!(result.is_ok());
     let result = result.unwrap();
     assert_eq!(result, data.len());
 
-    let result = server.recv(&mut read_data, 0);
+    let result = server.recv(&mut read_data, MsgFlags::empty());
     assert!(result.is_ok());
     let result_len = result.unwrap();
     assert_eq!(result_len, data.len());
     assert_eq!(read_data[result_len], 0);
     assert_eq!(&read_data[..result_len], data);
 
-    let result = server.recv(&mut read_data, 0);
+    let result = server.recv(&mut read_data, MsgFlags::empty());
     assert!(result.is_ok());
     let result_len = result.unwrap();
     assert_eq!(result_len, data.len());
@@ -105,8 +105,8 @@ fn socket_test_udp() {
 
 #[test]
 fn socket_test_tcp() {
-    let family = Family::IPv4;
-    let ty = Type::STREAM;
+    let family = Domain::IPv4;
+    let ty = SockType::STREAM;
     let proto = Protocol::TCP;
     let data = [1, 2, 3, 4];
     let server_addr = net::SocketAddr::from_str("127.0.0.1:60000").unwrap();
@@ -131,7 +131,7 @@ fn socket_test_tcp() {
         assert_eq!(result_addr, client_addr);
 
         let mut buf = [0; 10];
-        let result = result_socket.recv(&mut buf, 0);
+        let result = result_socket.recv(&mut buf, MsgFlags::empty());
         assert!(result.is_ok());
         let result_len = result.unwrap();
         assert_eq!(result_len, data.len());
@@ -141,15 +141,15 @@ fn socket_test_tcp() {
 
     let result = client.connect(&server_addr);
     assert!(result.is_ok());
-    assert!(client.send(&data, 0).is_ok());
+    assert!(client.send(&data, MsgFlags::empty()).is_ok());
 
     assert!(th.join().is_ok());
 }
 
 #[test]
 fn socket_test_tcp6() {
-    let family = Family::IPv6;
-    let ty = Type::STREAM;
+    let family = Domain::IPv6;
+    let ty = SockType::STREAM;
     let proto = Protocol::TCP;
     let data = [1, 2, 3, 4];
     let server_addr = net::SocketAddr::from_str("[::1]:60000").unwrap();
@@ -175,15 +175,15 @@ fn socket_test_tcp6() {
 
         // Check whether the `NON_INHERITABLE` flag worked
         let result = result_socket.get_inheritable();
-        assert!(result.is_ok() && result.unwrap() == false);
+        assert!(result.is_ok() && !result.unwrap());
 
 		// Check whether the `NON_BLOCKING` flag worked
         let mut buf = [0; 10];
-        let result = result_socket.recv(&mut buf, 0);
+        let result = result_socket.recv(&mut buf, MsgFlags::empty());
         assert!(result.is_err() && result.unwrap_err().kind() == std::io::ErrorKind::WouldBlock);
         assert!(result_socket.set_blocking(true).is_ok());
 
-        let result = result_socket.recv(&mut buf, 0);
+        let result = result_socket.recv(&mut buf, MsgFlags::empty());
 
         assert!(result.is_ok());
         let result_len = result.unwrap();
@@ -196,7 +196,7 @@ fn socket_test_tcp6() {
     assert!(result.is_ok());
 
     thread::sleep(time::Duration::from_millis(50));
-    assert!(client.send(&data, 0).is_ok());
+    assert!(client.send(&data, MsgFlags::empty()).is_ok());
 
     assert!(th.join().is_ok());
 }
@@ -213,7 +213,7 @@ fn socket_test_options() {
     #[cfg(unix)]
     let name: c_int = libc::SO_REUSEADDR; //SO_REUSEADDR
 
-    let socket = Socket::new(Family::IPv4, Type::STREAM, Protocol::TCP).unwrap();
+    let socket = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
 
     //On unix even bool options are of int type.
     let result = socket.get_opt::<c_int>(level, name);
@@ -246,7 +246,7 @@ fn socket_as_into_from_traits() {
     let raw_socket;
 
     {
-        let socket = Socket::new(Family::IPv4, Type::STREAM, Protocol::TCP).unwrap();
+        let socket = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
         raw_socket = socket.into_raw_socket();
     }
 
@@ -268,7 +268,7 @@ fn socket_as_into_from_traits() {
     let raw_socket;
 
     {
-        let socket = Socket::new(Family::IPv4, Type::STREAM, Protocol::TCP).unwrap();
+        let socket = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
         raw_socket = socket.into_raw_fd();
     }
 
@@ -288,7 +288,7 @@ fn socket_select_timeout() {
 
     let server_addr = net::SocketAddr::from_str("222.0.0.1:60004").unwrap();
 
-    let client = Socket::new(Family::IPv4, Type::STREAM, Protocol::TCP).unwrap();
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
 
     assert!(client.set_blocking(false).is_ok());
     let result = client.connect(&server_addr);
@@ -312,8 +312,8 @@ fn socket_select_connect() {
     #[cfg(unix)]
     let would_block_errno = libc::EINPROGRESS;
 
-    let family = Family::IPv4;
-    let ty = Type::STREAM;
+    let family = Domain::IPv4;
+    let ty = SockType::STREAM;
     let proto = Protocol::TCP;
     //Google DNS
     let server_addr = net::SocketAddr::from_str("8.8.8.8:53").unwrap();
@@ -329,3 +329,1100 @@ fn socket_select_connect() {
     assert!(result.is_ok());
     assert_eq!(result.unwrap(), 1);
 }
+
+#[cfg(unix)]
+#[test]
+fn socket_select_descriptor_range() {
+    let socket = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+
+    //Descriptors beyond `fd_set` are rejected.
+    if let Ok(high) = unsafe { socket.dup_to(libc::FD_SETSIZE as c_int + 100) } {
+        let result = lazy_socket::raw::select(&[], &[&high], &[], Some(0));
+        assert!(result.is_err() && result.err().unwrap().kind() == std::io::ErrorKind::InvalidInput);
+    }
+
+    //Descriptor 0 is valid one.
+    let stdin = unsafe { libc::dup(0) };
+    assert!(stdin >= 0);
+    let zero = unsafe { socket.dup_to(0) }.unwrap();
+    let result = lazy_socket::raw::select(&[], &[&zero], &[], Some(1000));
+    drop(zero);
+    unsafe {
+        libc::dup2(stdin, 0);
+        libc::close(stdin);
+    }
+    assert_eq!(result.unwrap(), 1);
+}
+
+#[test]
+fn socket_connect_timeout() {
+    let timeout = time::Duration::from_millis(500);
+
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(1).is_ok());
+    let server_addr = server.name().unwrap();
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(client.connect_timeout(&server_addr, timeout).is_ok());
+    assert_eq!(client.name().unwrap(), server.accept().unwrap().1);
+
+    //Original blocking mode is restored
+    #[cfg(unix)]
+    {
+        let flags = unsafe { libc::fcntl(client.raw(), libc::F_GETFL) };
+        assert_eq!(flags & libc::O_NONBLOCK, 0);
+    }
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(client.set_blocking(false).is_ok());
+    assert!(client.connect_timeout(&server_addr, timeout).is_ok());
+    #[cfg(unix)]
+    {
+        let flags = unsafe { libc::fcntl(client.raw(), libc::F_GETFL) };
+        assert_ne!(flags & libc::O_NONBLOCK, 0);
+    }
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    let result = client.connect_timeout(&server_addr, time::Duration::from_secs(0));
+    assert!(result.is_err() && result.err().unwrap().kind() == std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn socket_connect_timeout_refused() {
+    //Reserve port and make sure nobody listens on it.
+    let server_addr = {
+        let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+        assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+        server.name().unwrap()
+    };
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    let result = client.connect_timeout(&server_addr, time::Duration::from_millis(500));
+    assert!(result.is_err() && result.err().unwrap().kind() == std::io::ErrorKind::ConnectionRefused);
+}
+
+#[test]
+fn socket_peer_name() {
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(1).is_ok());
+    let server_addr = server.name().unwrap();
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    let error = client.peer_name().unwrap_err();
+    assert_eq!(error.operation(), Operation::GetPeerName);
+    assert_eq!(error.kind(), std::io::ErrorKind::NotConnected);
+
+    assert!(client.connect(&server_addr).is_ok());
+    assert_eq!(client.peer_name().unwrap(), server_addr);
+    let (accepted, client_addr) = server.accept().unwrap();
+    assert_eq!(accepted.peer_name().unwrap(), client_addr);
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_take_error() {
+    let server_addr = {
+        let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+        assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+        server.name().unwrap()
+    };
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(client.take_error().unwrap().is_none());
+
+    client.set_blocking(false).unwrap();
+    let _ = client.connect(&server_addr);
+    let mut pollfd = libc::pollfd { fd: client.raw(), events: libc::POLLOUT, revents: 0 };
+    assert_eq!(unsafe { libc::poll(&mut pollfd, 1, 1000) }, 1);
+
+    let error = client.take_error().unwrap().expect("Pending error");
+    assert_eq!(error.operation(), Operation::Connect);
+    assert_eq!(error.kind(), std::io::ErrorKind::ConnectionRefused);
+    //Error is cleared once taken.
+    assert!(client.take_error().unwrap().is_none());
+}
+
+#[test]
+fn socket_connect_timeout_expired() {
+    let timeout = time::Duration::from_millis(100);
+
+    //Listener that never accepts drops new connection requests once its backlog is full.
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(0).is_ok());
+    let server_addr = server.name().unwrap();
+
+    let mut clients = Vec::new();
+    let timed_out = (0..16).any(|_| {
+        let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+        let now = time::Instant::now();
+        let result = client.connect_timeout(&server_addr, timeout);
+        clients.push(client);
+
+        match result {
+            Ok(()) => false,
+            Err(error) => {
+                assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+                assert!(now.elapsed() >= timeout);
+                true
+            }
+        }
+    });
+
+    assert!(timed_out);
+}
+
+#[cfg(unix)]
+fn tcp_pair() -> (Socket, Socket) {
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(1).is_ok());
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(client.connect(&server.name().unwrap()).is_ok());
+    let (accepted, _) = server.accept().unwrap();
+
+    (client, accepted)
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_send_broken_pipe() {
+    use std::io::ErrorKind;
+
+    //No SIGPIPE handler is installed, so signal would kill test process.
+    let (client, accepted) = tcp_pair();
+    drop(accepted);
+
+    let mut error = None;
+    for _ in 0..100 {
+        match client.send(&[1, 2, 3, 4], MsgFlags::empty()) {
+            Ok(_) => thread::sleep(time::Duration::from_millis(10)),
+            //Reset by peer might be reported first.
+            Err(ref err) if err.kind() == ErrorKind::ConnectionReset => (),
+            Err(err) => {
+                error = Some(err);
+                break;
+            }
+        }
+    }
+    assert_eq!(error.unwrap().kind(), ErrorKind::BrokenPipe);
+
+    let result = client.send_vectored(&[std::io::IoSlice::new(&[1])], MsgFlags::empty());
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::BrokenPipe);
+    let result = std::io::Write::write(&mut &client, &[1]);
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::BrokenPipe);
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_drop_keeps_dup_connected() {
+    use std::os::unix::io::FromRawFd;
+
+    let data = [1, 2, 3, 4];
+    let (client, accepted) = tcp_pair();
+
+    let dup = unsafe { Socket::from_raw_fd(libc::dup(client.raw())) };
+    drop(client);
+
+    assert_eq!(dup.send(&data, MsgFlags::empty()).unwrap(), data.len());
+    let mut buf = [0; 10];
+    assert_eq!(accepted.recv(&mut buf, MsgFlags::empty()).unwrap(), data.len());
+    assert_eq!(&buf[..data.len()], data);
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_drop_keeps_child_connected() {
+    let data = [1, 2, 3, 4];
+    let (client, accepted) = tcp_pair();
+
+    let mut notify = [0 as c_int; 2];
+    assert_eq!(unsafe { libc::pipe(notify.as_mut_ptr()) }, 0);
+
+    match unsafe { libc::fork() } {
+        -1 => panic!("fork() failed"),
+        0 => unsafe {
+            //Child waits for parent to drop its socket and then uses inherited one.
+            let mut byte = 0u8;
+            libc::read(notify[0], &mut byte as *mut u8 as *mut c_void, 1);
+            let sent = libc::send(client.raw(), data.as_ptr() as *const c_void, data.len(), 0);
+            libc::_exit(if sent == data.len() as isize { 0 } else { 1 });
+        },
+        child => {
+            drop(client);
+            assert_eq!(unsafe { libc::write(notify[1], &1u8 as *const u8 as *const c_void, 1) }, 1);
+
+            let mut buf = [0; 10];
+            assert_eq!(accepted.recv(&mut buf, MsgFlags::empty()).unwrap(), data.len());
+            assert_eq!(&buf[..data.len()], data);
+
+            let mut status = 0;
+            assert_eq!(unsafe { libc::waitpid(child, &mut status, 0) }, child);
+            assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+
+            unsafe {
+                libc::close(notify[0]);
+                libc::close(notify[1]);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_shutdown_on_drop() {
+    use std::os::unix::io::FromRawFd;
+
+    let (client, accepted) = tcp_pair();
+
+    let dup = unsafe { Socket::from_raw_fd(libc::dup(client.raw())) };
+    let client = ShutdownOnDrop::new(client, ShutdownType::Both);
+    assert!(client.name().is_ok());
+    drop(client);
+
+    //Connection is shut down even though duplicate is still open.
+    let mut buf = [0; 10];
+    assert_eq!(accepted.recv(&mut buf, MsgFlags::empty()).unwrap(), 0);
+    assert!(dup.send(&[1], MsgFlags::empty()).is_err());
+
+    //Unwrapped socket is not shut down.
+    let (client, accepted) = tcp_pair();
+    let client = ShutdownOnDrop::new(client, ShutdownType::Both).into_inner();
+    assert_eq!(client.send(&[1], MsgFlags::empty()).unwrap(), 1);
+    assert_eq!(accepted.recv(&mut buf, MsgFlags::empty()).unwrap(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_close_reports_error() {
+    use std::os::unix::io::FromRawFd;
+
+    let socket = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(socket.close().is_ok());
+
+    //Invalid descriptor, so that failure doesn't affect others in parallel tests.
+    let closed = unsafe { Socket::from_raw_fd(-1) };
+
+    let result = closed.close();
+    assert!(result.is_err());
+    assert_eq!(result.err().unwrap().raw_os_error(), Some(libc::EBADF));
+}
+
+#[test]
+fn socket_try_clone() {
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.set_inheritable(true).is_ok());
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(1).is_ok());
+    let server_addr = server.name().unwrap();
+
+    let clone = server.try_clone().unwrap();
+    assert!(!clone.get_inheritable().unwrap());
+    assert_eq!(clone.name().unwrap(), server_addr);
+    drop(server);
+
+    //Clone keeps listening after original is gone.
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(client.connect(&server_addr).is_ok());
+    assert_eq!(clone.accept().unwrap().1, client.name().unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_dup_to() {
+    use std::os::unix::io::IntoRawFd;
+
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.set_inheritable(false).is_ok());
+
+    //Reserve descriptor number that we own.
+    let target = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap().into_raw_fd();
+
+    let dup = unsafe { server.dup_to(target) }.unwrap();
+    assert_eq!(dup.raw(), target);
+    assert!(dup.get_inheritable().unwrap());
+    assert_eq!(dup.name().unwrap(), server.name().unwrap());
+
+    let result = unsafe { server.dup_to(server.raw()) };
+    assert!(result.is_err() && result.err().unwrap().kind() == std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn socket_std_conversions() {
+    use std::convert::TryFrom;
+
+    let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let listener_addr = listener.local_addr().unwrap();
+
+    let socket = Socket::from(listener);
+    assert_eq!(socket.name().unwrap(), listener_addr);
+    assert!(net::UdpSocket::try_from(socket).is_err());
+
+    let socket = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(socket.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(socket.listen(1).is_ok());
+    //Listening socket is not a stream.
+    let result = net::TcpStream::try_from(socket.try_clone().unwrap());
+    assert!(result.is_err() && result.err().unwrap().kind() == std::io::ErrorKind::InvalidInput);
+    let listener = net::TcpListener::try_from(socket).unwrap();
+
+    let stream = net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let stream_addr = stream.local_addr().unwrap();
+    let socket = Socket::from(stream);
+    assert_eq!(socket.name().unwrap(), stream_addr);
+    //Connected socket is not a listener.
+    assert!(net::TcpListener::try_from(socket.try_clone().unwrap()).is_err());
+    let stream = net::TcpStream::try_from(socket).unwrap();
+    assert_eq!(listener.accept().unwrap().1, stream.local_addr().unwrap());
+
+    let socket = Socket::new(Domain::IPv6, SockType::DATAGRAM, Protocol::UDP).unwrap();
+    let result = net::TcpStream::try_from(socket);
+    assert!(result.is_err() && result.err().unwrap().kind() == std::io::ErrorKind::InvalidInput);
+
+    let socket = Socket::new(Domain::IPv6, SockType::DATAGRAM, Protocol::UDP).unwrap();
+    assert!(net::UdpSocket::try_from(socket).is_ok());
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_std_unix_conversions() {
+    use std::convert::TryFrom;
+    use std::os::unix::net::{UnixDatagram, UnixStream, UnixListener};
+
+    let (left, right) = UnixStream::pair().unwrap();
+    let socket = Socket::from(left);
+    assert_eq!(socket.send(&[1, 2], MsgFlags::empty()).unwrap(), 2);
+    assert!(net::TcpStream::try_from(socket.try_clone().unwrap()).is_err());
+    assert!(UnixListener::try_from(socket.try_clone().unwrap()).is_err());
+    let left = UnixStream::try_from(socket).unwrap();
+    drop(left);
+
+    let mut buf = [0; 4];
+    assert_eq!(Socket::from(right).recv(&mut buf, MsgFlags::empty()).unwrap(), 2);
+
+    let (left, _right) = UnixDatagram::pair().unwrap();
+    let socket = Socket::from(left);
+    assert!(UnixStream::try_from(socket.try_clone().unwrap()).is_err());
+    assert!(net::UdpSocket::try_from(socket.try_clone().unwrap()).is_err());
+    assert!(UnixDatagram::try_from(socket).is_ok());
+
+    let listener = UnixListener::bind(std::env::temp_dir().join(format!("lazy_socket_conversions_{}", std::process::id()))).unwrap();
+    let path = listener.local_addr().unwrap().as_pathname().unwrap().to_owned();
+    let socket = Socket::from(listener);
+    assert!(UnixStream::try_from(socket.try_clone().unwrap()).is_err());
+    assert!(UnixListener::try_from(socket).is_ok());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_owned_borrowed_fd() {
+    use std::os::unix::io::{AsFd, AsRawFd, OwnedFd};
+
+    let socket = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+    let raw = socket.raw();
+    assert_eq!(socket.as_fd().as_raw_fd(), raw);
+
+    let owned = OwnedFd::from(socket);
+    assert_eq!(owned.as_raw_fd(), raw);
+    let socket = Socket::from(owned);
+    assert_eq!(socket.raw(), raw);
+}
+
+#[test]
+fn socket_ref() {
+    let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let listener_addr = listener.local_addr().unwrap();
+    let stream = net::TcpStream::connect(listener_addr).unwrap();
+    let (accepted, _) = listener.accept().unwrap();
+
+    {
+        let socket = SocketRef::from(&stream);
+        assert_eq!(socket.name().unwrap(), stream.local_addr().unwrap());
+        assert_eq!(socket.send(&[1, 2, 3], MsgFlags::empty()).unwrap(), 3);
+
+        let accepted = SocketRef::from(&accepted);
+        assert_eq!(lazy_socket::raw::select(&[&accepted], &[], &[], Some(1000)).unwrap(), 1);
+    }
+
+    //Borrowed sockets can be passed to select without wrapping.
+    assert_eq!(lazy_socket::raw::select(&[&accepted], &[&stream], &[], Some(1000)).unwrap(), 2);
+
+    //Borrowed sockets stay open.
+    assert_eq!(stream.local_addr().unwrap(), SocketRef::from(&stream).name().unwrap());
+    let mut buf = [0; 10];
+    assert_eq!(SocketRef::from(&accepted).recv(&mut buf, MsgFlags::empty()).unwrap(), 3);
+}
+
+#[test]
+fn socket_cloexec_by_default() {
+    let socket = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(!socket.get_inheritable().unwrap());
+
+    let socket = Socket::new_inheritable(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(socket.get_inheritable().unwrap());
+
+    let server = Socket::new_inheritable(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(2).is_ok());
+    let server_addr = server.name().unwrap();
+
+    let _client = net::TcpStream::connect(server_addr).unwrap();
+    let (accepted, _) = server.accept().unwrap();
+    assert!(!accepted.get_inheritable().unwrap());
+
+    let _client = net::TcpStream::connect(server_addr).unwrap();
+    let (accepted, _) = server.accept4(AcceptFlags::empty()).unwrap();
+    assert!(accepted.get_inheritable().unwrap());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn socket_recv_msg_cloexec_fds() {
+    use std::mem;
+    use std::ptr;
+    use std::os::unix::net::UnixStream;
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+
+    let (left, right) = UnixStream::pair().unwrap();
+    let passed = Socket::new_inheritable(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+
+    unsafe {
+        let mut data = [0u8; 1];
+        let mut iov = libc::iovec { iov_base: data.as_mut_ptr() as *mut c_void, iov_len: data.len() };
+        let mut control = [0u8; 64];
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr() as *mut c_void;
+        msg.msg_controllen = libc::CMSG_SPACE(mem::size_of::<c_int>() as u32) as usize;
+
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(mem::size_of::<c_int>() as u32) as usize;
+        ptr::write_unaligned(libc::CMSG_DATA(cmsg) as *mut c_int, passed.raw());
+
+        assert_eq!(libc::sendmsg(left.as_raw_fd(), &msg, 0), 1);
+    }
+
+    let right = Socket::from(right);
+    let mut buf = [0u8; 1];
+    let mut control = [0u8; 64];
+    let result = right.recv_msg(&mut [std::io::IoSliceMut::new(&mut buf)], &mut control, MsgFlags::empty()).unwrap();
+    assert_eq!(result.len, 1);
+    assert!(result.control_len > 0);
+
+    let received = unsafe {
+        let mut msg: libc::msghdr = mem::zeroed();
+        msg.msg_control = control.as_mut_ptr() as *mut c_void;
+        msg.msg_controllen = result.control_len;
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        assert_eq!((*cmsg).cmsg_type, libc::SCM_RIGHTS);
+        Socket::from_raw_fd(ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const c_int))
+    };
+
+    assert!(!received.get_inheritable().unwrap());
+}
+
+#[test]
+fn socket_typed_domain_type_protocol() {
+    assert_eq!(Domain::IPv4.to_string(), "AF_INET");
+    assert_eq!(SockType::DATAGRAM.to_string(), "SOCK_DGRAM");
+    assert_eq!(Protocol::TCP.to_string(), "IPPROTO_TCP");
+    assert_eq!(Protocol::from(253).to_string(), "253");
+
+    let domain: c_int = Domain::IPv6.into();
+    assert_eq!(Domain::from(domain), Domain::IPv6);
+
+    #[cfg(target_os = "linux")]
+    {
+        assert_eq!(SockType::STREAM.nonblocking().cloexec().to_string(), "SOCK_STREAM | SOCK_NONBLOCK | SOCK_CLOEXEC");
+
+        let socket = Socket::new_inheritable(Domain::IPv4, SockType::STREAM.nonblocking(), Protocol::TCP).unwrap();
+        let flags = unsafe { libc::fcntl(socket.raw(), libc::F_GETFL) };
+        assert_ne!(flags & libc::O_NONBLOCK, 0);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_builder() {
+    let any_addr = net::SocketAddr::from_str("127.0.0.1:0").unwrap();
+
+    let server = SocketBuilder::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).reuse_address(true)
+                                                                                 .reuse_port(true)
+                                                                                 .recv_buffer_size(64 * 1024)
+                                                                                 .bind(any_addr)
+                                                                                 .listen(8)
+                                                                                 .unwrap();
+    let server_addr = server.name().unwrap();
+    assert_ne!(server.get_opt::<c_int>(libc::SOL_SOCKET, libc::SO_REUSEADDR).unwrap(), 0);
+    assert_ne!(server.get_opt::<c_int>(libc::SOL_SOCKET, libc::SO_REUSEPORT).unwrap(), 0);
+    assert!(server.get_opt::<c_int>(libc::SOL_SOCKET, libc::SO_RCVBUF).unwrap() >= 64 * 1024);
+
+    //Builder can be reused to create another listener on the same port thanks to SO_REUSEPORT.
+    let builder = SocketBuilder::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).reuse_port(true).bind(server_addr);
+    assert!(builder.listen(8).is_ok());
+
+    //Without SO_REUSEPORT bind fails.
+    let result = SocketBuilder::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).bind(server_addr).listen(8);
+    assert!(result.is_err() && result.err().unwrap().kind() == std::io::ErrorKind::AddrInUse);
+
+    let client = SocketBuilder::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).nodelay(true)
+                                                                                 .read_timeout(time::Duration::from_millis(50))
+                                                                                 .connect(&server_addr)
+                                                                                 .unwrap();
+    assert_ne!(client.get_opt::<c_int>(libc::IPPROTO_TCP, libc::TCP_NODELAY).unwrap(), 0);
+
+    let mut buf = [0; 4];
+    let result = client.recv(&mut buf, MsgFlags::empty());
+    assert!(result.is_err() && result.err().unwrap().kind() == std::io::ErrorKind::WouldBlock);
+
+    let server6 = SocketBuilder::new(Domain::IPv6, SockType::STREAM, Protocol::TCP).only_v6(true)
+                                                                                  .bind(net::SocketAddr::from_str("[::1]:0").unwrap())
+                                                                                  .build()
+                                                                                  .unwrap();
+    assert_ne!(server6.get_opt::<c_int>(libc::IPPROTO_IPV6, libc::IPV6_V6ONLY).unwrap(), 0);
+}
+
+#[test]
+fn socket_incoming() {
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(4).is_ok());
+    let server_addr = server.name().unwrap();
+
+    let clients: Vec<net::TcpStream> = (0..3).map(|_| net::TcpStream::connect(server_addr).unwrap()).collect();
+
+    for (client, result) in clients.iter().zip(server.incoming(NON_INHERITABLE)) {
+        let (socket, addr) = result.unwrap();
+        assert_eq!(addr, client.local_addr().unwrap());
+        assert!(!socket.get_inheritable().unwrap());
+    }
+
+    //Errors other than interruption are reported.
+    assert!(server.set_blocking(false).is_ok());
+    let result = server.incoming(AcceptFlags::empty()).next().unwrap();
+    assert!(result.is_err() && result.err().unwrap().kind() == std::io::ErrorKind::WouldBlock);
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_read_write() {
+    use std::io::{BufRead, BufReader, IoSlice, IoSliceMut, Read, Write};
+
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(1).is_ok());
+
+    let mut client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(client.connect(&server.name().unwrap()).is_ok());
+    let (accepted, _) = server.accept().unwrap();
+
+    assert!(client.write_all(b"hello\n").is_ok());
+    assert_eq!((&client).write_vectored(&[IoSlice::new(b"wor"), IoSlice::new(b"ld\n")]).unwrap(), 6);
+    assert!(client.flush().is_ok());
+
+    let mut reader = BufReader::new(&accepted);
+    let mut line = String::new();
+    assert!(reader.read_line(&mut line).is_ok());
+    assert_eq!(line, "hello\n");
+    line.clear();
+    assert!(reader.read_line(&mut line).is_ok());
+    assert_eq!(line, "world\n");
+
+    assert!((&accepted).write_all(&[1, 2, 3, 4, 5]).is_ok());
+    let mut head = [0; 2];
+    let mut tail = [0; 3];
+    assert_eq!(client.read_vectored(&mut [IoSliceMut::new(&mut head), IoSliceMut::new(&mut tail)]).unwrap(), 5);
+    assert_eq!(head, [1, 2]);
+    assert_eq!(tail, [3, 4, 5]);
+
+    //io::copy until peer closes connection.
+    assert!((&accepted).write_all(b"tail").is_ok());
+    drop(reader);
+    drop(accepted);
+    let mut rest = Vec::new();
+    assert_eq!(std::io::copy(&mut client, &mut rest).unwrap(), 4);
+    assert_eq!(rest, b"tail");
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_msg_flags() {
+    use std::io::IoSliceMut;
+
+    let any_addr = net::SocketAddr::from_str("127.0.0.1:0").unwrap();
+    let server = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+    assert!(server.bind(&any_addr).is_ok());
+    let server_addr = server.name().unwrap();
+
+    let client = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+    let mut buf = [0; 4];
+
+    let result = server.recv(&mut buf, DONTWAIT);
+    assert!(result.is_err() && result.err().unwrap().kind() == std::io::ErrorKind::WouldBlock);
+
+    assert_eq!(client.send_to(&[1, 2, 3, 4, 5, 6], &server_addr, MsgFlags::empty()).unwrap(), 6);
+
+    //Peeked datagram stays in queue.
+    assert_eq!(server.recv(&mut buf[..2], PEEK).unwrap(), 2);
+    assert_eq!(buf[..2], [1, 2]);
+
+    let result = server.recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut [], MsgFlags::empty()).unwrap();
+    assert_eq!(result.len, 4);
+    assert_eq!(buf, [1, 2, 3, 4]);
+    assert!(result.flags.contains(TRUNC));
+    assert_eq!(result.addr.unwrap().port(), client.name().unwrap().port());
+
+    assert_eq!(client.send_to(&[1, 2], &server_addr, MsgFlags::empty()).unwrap(), 2);
+    let result = server.recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut [], MsgFlags::empty()).unwrap();
+    assert_eq!(result.len, 2);
+    assert!(!result.flags.contains(TRUNC));
+}
+
+#[test]
+fn socket_error() {
+    use std::io;
+
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(1).is_ok());
+    let addr = server.name().unwrap();
+
+    let socket = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    let error = socket.bind(&addr).unwrap_err();
+    assert_eq!(error.operation(), Operation::Bind);
+    assert_eq!(error.addr(), Some(addr));
+    assert_eq!(error.class(), ErrorClass::AddrInUse);
+    assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+    assert!(error.raw_os_error().is_some());
+    #[cfg(unix)]
+    assert_eq!(error.to_string(), format!("bind {}: EADDRINUSE", addr));
+    #[cfg(windows)]
+    assert_eq!(error.to_string(), format!("bind {}: WSAEADDRINUSE", addr));
+
+    let message = error.to_string();
+    let code = error.raw_os_error();
+    let error = io::Error::from(error);
+    assert_eq!(error.kind(), io::ErrorKind::AddrInUse);
+    assert_eq!(error.to_string(), message);
+    assert_eq!(error.get_ref().unwrap().downcast_ref::<SocketError>().unwrap().raw_os_error(), code);
+
+    let error = socket.set_opt(-1, -1, 1 as c_int).unwrap_err();
+    assert_eq!(error.operation(), Operation::SetOpt { level: -1, name: -1 });
+    assert!(error.to_string().starts_with("setsockopt(-1, -1): "));
+
+    let error = socket.set_read_timeout(Some(time::Duration::from_secs(0))).unwrap_err();
+    assert_eq!(error.class(), ErrorClass::InvalidInput);
+    assert_eq!(error.raw_os_error(), None);
+
+    let message = error.to_string();
+    let error = io::Error::from(error);
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(error.to_string(), message);
+    assert!(error.get_ref().unwrap().downcast_ref::<SocketError>().is_some());
+
+    //Send after shutdown is reported as error.
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(client.connect(&addr).is_ok());
+    assert!(client.shutdown(ShutdownType::Send).is_ok());
+    let error = client.send(&[1], MsgFlags::empty()).unwrap_err();
+    assert_eq!(error.operation(), Operation::Send);
+    assert_eq!(error.class(), ErrorClass::BrokenPipe);
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_sockaddr_to_addr() {
+    use std::mem;
+    use std::ptr;
+    use lazy_socket::raw::fuzzing::{get_raw_addr, sockaddr_to_addr};
+
+    let storage_len = mem::size_of::<libc::sockaddr_storage>();
+
+    for addr in &["127.0.0.1:80", "[::1]:8080"] {
+        let addr = net::SocketAddr::from_str(addr).unwrap();
+        let (storage, len) = get_raw_addr(&addr);
+        assert_eq!(sockaddr_to_addr(&storage, len).unwrap(), addr);
+
+        //Truncated address is error rather than panic.
+        for short_len in 0..len {
+            let error = sockaddr_to_addr(&storage, short_len).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
+
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    storage.ss_family = libc::AF_UNIX as libc::sa_family_t;
+    assert!(sockaddr_to_addr(&storage, storage_len as libc::socklen_t).is_err());
+
+    //Arbitrary bytes and lengths, generated by xorshift to keep test reproducible.
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    for _ in 0..100_000 {
+        let mut bytes = vec![0u8; storage_len];
+        for byte in bytes.iter_mut() {
+            *byte = next() as u8;
+        }
+
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), &mut storage as *mut _ as *mut u8, storage_len) };
+        //Bias family towards IP ones, so that length checks are exercised.
+        match next() % 3 {
+            0 => storage.ss_family = libc::AF_INET as libc::sa_family_t,
+            1 => storage.ss_family = libc::AF_INET6 as libc::sa_family_t,
+            _ => ()
+        }
+        let len = match next() % 4 {
+            0 => next() as libc::socklen_t,
+            _ => (next() % (storage_len as u64 + 1)) as libc::socklen_t
+        };
+
+        match sockaddr_to_addr(&storage, len) {
+            Ok(net::SocketAddr::V4(_)) => {
+                assert_eq!(storage.ss_family as c_int, libc::AF_INET);
+                assert!(len as usize >= mem::size_of::<libc::sockaddr_in>());
+            },
+            Ok(net::SocketAddr::V6(_)) => {
+                assert_eq!(storage.ss_family as c_int, libc::AF_INET6);
+                assert!(len as usize >= mem::size_of::<libc::sockaddr_in6>());
+            },
+            Err(error) => assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput)
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_recv_from_unnamed_peer() {
+    use std::os::unix::io::FromRawFd;
+
+    let mut fds = [0 as c_int; 2];
+    assert_eq!(unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_DGRAM, 0, fds.as_mut_ptr()) }, 0);
+    let (left, right) = unsafe { (Socket::from_raw_fd(fds[0]), Socket::from_raw_fd(fds[1])) };
+
+    assert_eq!(left.send(&[1, 2, 3], MsgFlags::empty()).unwrap(), 3);
+    let mut buf = [0; 3];
+    //Unnamed Unix peer has no IP address, which is reported as error.
+    let error = right.recv_from(&mut buf, MsgFlags::empty()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(right.name().is_err());
+}
+
+#[test]
+fn socket_send_all_recv_exact() {
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(1).is_ok());
+    let addr = server.name().unwrap();
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(client.connect(&addr).is_ok());
+    let (accepted, _) = server.accept().unwrap();
+
+    //Large enough to require partial sends.
+    let data: Vec<u8> = (0..4 * 1024 * 1024).map(|idx| idx as u8).collect();
+    let expected = data.clone();
+    let th = thread::spawn(move || {
+        assert!(client.send_all(&data).is_ok());
+        assert!(client.send_all(b"first\nsecond\nno delimiter").is_ok());
+    });
+
+    let mut buf = vec![0; expected.len()];
+    assert!(accepted.recv_exact(&mut buf).is_ok());
+    assert!(buf == expected);
+    assert!(th.join().is_ok());
+
+    assert_eq!(accepted.recv_until(b'\n', 64).unwrap(), b"first\n");
+    //Too short limit consumes data up to it.
+    let error = accepted.recv_until(b'\n', 3).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(accepted.recv_until(b'\n', 64).unwrap(), b"ond\n");
+    //Data past delimiter is not consumed.
+    let mut buf = [0; 2];
+    assert!(accepted.recv_exact(&mut buf).is_ok());
+    assert_eq!(&buf, b"no");
+    assert_eq!(accepted.recv_until(b'\n', 64).unwrap(), b" delimiter");
+    assert_eq!(accepted.recv_until(b'\n', 64).unwrap(), b"");
+
+    let error = accepted.recv_exact(&mut buf).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_peek_and_queues() {
+    let (client, accepted) = tcp_pair();
+    let mut buf = [0; 10];
+
+    assert_eq!(accepted.bytes_available().unwrap(), 0);
+    assert!(client.send_all(b"hello").is_ok());
+    thread::sleep(time::Duration::from_millis(50));
+
+    assert_eq!(accepted.bytes_available().unwrap(), 5);
+    assert_eq!(accepted.peek(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"hello");
+    assert_eq!(accepted.bytes_available().unwrap(), 5);
+    assert!(accepted.recv_exact(&mut buf[..5]).is_ok());
+    assert_eq!(accepted.bytes_available().unwrap(), 0);
+
+    //Send queue fills up once peer stops receiving.
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios"))]
+    {
+        assert_eq!(client.send_queue_len().unwrap(), 0);
+        assert!(client.set_blocking(false).is_ok());
+        let data = [0; 64 * 1024];
+        while client.send(&data, MsgFlags::empty()).is_ok() {}
+        assert!(client.send_queue_len().unwrap() > 0);
+    }
+}
+
+#[test]
+fn socket_peek_from() {
+    let server = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    let client = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+    assert!(client.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+
+    assert_eq!(client.send_to(b"hello", &server.name().unwrap(), MsgFlags::empty()).unwrap(), 5);
+    let mut buf = [0; 10];
+    for _ in 0..2 {
+        let (len, addr) = server.peek_from(&mut buf).unwrap();
+        assert_eq!(len, 5);
+        assert_eq!(addr, client.name().unwrap());
+    }
+    assert_eq!(server.bytes_available().unwrap(), 5);
+    assert_eq!(server.recv(&mut buf, MsgFlags::empty()).unwrap(), 5);
+    assert_eq!(&buf[..5], b"hello");
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_oob() {
+    let (client, accepted) = tcp_pair();
+    let mut buf = [0; 10];
+
+    assert_eq!(client.send(b"ab", MsgFlags::empty()).unwrap(), 2);
+    assert_eq!(client.send_oob(b"!").unwrap(), 1);
+    thread::sleep(time::Duration::from_millis(50));
+
+    assert!(!accepted.at_mark().unwrap());
+    //Receive stops at mark.
+    assert_eq!(accepted.recv(&mut buf, MsgFlags::empty()).unwrap(), 2);
+    assert_eq!(&buf[..2], b"ab");
+    assert!(accepted.at_mark().unwrap());
+    assert_eq!(accepted.recv_oob(&mut buf).unwrap(), 1);
+    assert_eq!(buf[0], b'!');
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_ioctl() {
+    let (client, accepted) = tcp_pair();
+    assert!(client.send_all(b"abc").is_ok());
+    thread::sleep(time::Duration::from_millis(50));
+
+    let mut available: c_int = 0;
+    assert!(unsafe { accepted.ioctl_mut(libc::FIONREAD as _, &mut available) }.is_ok());
+    assert_eq!(available, 3);
+    assert_eq!(unsafe { accepted.ioctl_get::<c_int>(libc::FIONREAD as _) }.unwrap(), 3);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn socket_interface_ioctl() {
+    let socket = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+
+    let index = socket.interface_index("lo").unwrap();
+    assert_eq!(index, unsafe { libc::if_nametoindex(b"lo\0".as_ptr() as *const _) });
+    assert!(socket.interface_mtu("lo").unwrap() > 0);
+    assert_eq!(socket.interface_hwaddr("lo").unwrap(), [0; 6]);
+    let flags = socket.interface_flags("lo").unwrap();
+    assert!(flags.contains(UP | LOOPBACK | RUNNING));
+    assert!(!flags.contains(BROADCAST));
+
+    let error = socket.interface_index("no-such-iface").unwrap_err();
+    assert_eq!(error.raw_os_error(), Some(libc::ENODEV));
+    assert_eq!(error.operation(), Operation::Ioctl);
+
+    let error = socket.interface_mtu("name-which-is-too-long").unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(socket.interface_flags("").is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_interfaces() {
+    let interfaces = interfaces().unwrap();
+    let lo = interfaces.iter().find(|interface| interface.flags.contains(LOOPBACK)).expect("No loopback interface");
+
+    let mut name = lo.name.clone().into_bytes();
+    name.push(0);
+    assert_eq!(lo.index, unsafe { libc::if_nametoindex(name.as_ptr() as *const _) });
+    assert!(lo.flags.contains(UP | RUNNING));
+
+    let localhost = InterfaceAddr { addr: net::IpAddr::from_str("127.0.0.1").unwrap(), prefix_len: 8 };
+    assert!(lo.addrs.contains(&localhost));
+    if let Some(addr) = lo.addrs.iter().find(|addr| addr.addr.is_ipv6()) {
+        assert_eq!(addr.addr, net::IpAddr::from_str("::1").unwrap());
+        assert_eq!(addr.prefix_len, 128);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        assert!(lo.mtu.unwrap() > 0);
+        assert_eq!(lo.hwaddr, Some([0; 6]));
+    }
+
+    //Each interface is listed once.
+    for (idx, interface) in interfaces.iter().enumerate() {
+        assert!(interfaces[idx + 1..].iter().all(|other| other.name != interface.name));
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_resolve() {
+    let addrs = resolve(Some("127.0.0.1"), 80, Domain::UNSPECIFIED, SockType::STREAM, NUMERICHOST).unwrap();
+    assert_eq!(addrs, [net::SocketAddr::from_str("127.0.0.1:80").unwrap()]);
+
+    let addrs = resolve(Some("::1"), 443, Domain::IPv6, SockType::STREAM, NUMERICHOST).unwrap();
+    assert_eq!(addrs, [net::SocketAddr::from_str("[::1]:443").unwrap()]);
+
+    //Taken from /etc/hosts
+    let addrs = resolve(Some("localhost"), 8080, Domain::IPv4, SockType::STREAM, ResolveFlags::empty()).unwrap();
+    assert_eq!(addrs, [net::SocketAddr::from_str("127.0.0.1:8080").unwrap()]);
+
+    let addrs = resolve(None, 8080, Domain::IPv4, SockType::DATAGRAM, PASSIVE).unwrap();
+    assert_eq!(addrs, [net::SocketAddr::from_str("0.0.0.0:8080").unwrap()]);
+    let addrs = resolve(None, 8080, Domain::IPv4, SockType::DATAGRAM, ResolveFlags::empty()).unwrap();
+    assert_eq!(addrs, [net::SocketAddr::from_str("127.0.0.1:8080").unwrap()]);
+
+    let error = resolve(Some("localhost"), 80, Domain::UNSPECIFIED, SockType::STREAM, NUMERICHOST).unwrap_err();
+    assert_eq!(error.operation(), Operation::Resolve);
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    assert!(error.to_string().starts_with("getaddrinfo: localhost: "));
+
+    let error = resolve(Some("local\0host"), 80, Domain::UNSPECIFIED, SockType::STREAM, ResolveFlags::empty()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_connect_host() {
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).unwrap();
+    server.listen(1).unwrap();
+    let port = server.name().unwrap().port();
+
+    let client = Socket::connect_host(&format!("localhost:{}", port)).unwrap();
+    let (_, client_addr) = server.accept().unwrap();
+    assert_eq!(client.name().unwrap(), client_addr);
+
+    let client = Socket::connect_host(&format!("[127.0.0.1]:{}", port)).unwrap();
+    let (_, client_addr) = server.accept().unwrap();
+    assert_eq!(client.name().unwrap(), client_addr);
+
+    for target in ["localhost", "localhost:", ":80", "::1:80", "localhost:65536"].iter() {
+        let error = Socket::connect_host(target).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    //Nothing listens there anymore.
+    drop(server);
+    let error = Socket::connect_host(&format!("localhost:{}", port)).err().unwrap();
+    assert_eq!(error.operation(), Operation::Connect);
+    assert_eq!(error.kind(), std::io::ErrorKind::ConnectionRefused);
+}
+
+#[cfg(unix)]
+///Returns listener, which silently drops further SYNs as its backlog is full, along with sockets filling it.
+fn blackhole() -> (Socket, Vec<Socket>) {
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).unwrap();
+    server.listen(0).unwrap();
+    let addr = server.name().unwrap();
+
+    let mut fillers = Vec::new();
+    for _ in 0..16 {
+        let filler = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+        let result = filler.connect_timeout(&addr, time::Duration::from_millis(200));
+        fillers.push(filler);
+
+        if let Err(error) = result {
+            assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+            return (server, fillers);
+        }
+    }
+
+    panic!("Backlog of listener is never full");
+}
+
+#[cfg(unix)]
+#[test]
+fn socket_happy_eyeballs() {
+    let (blackhole, _fillers) = blackhole();
+    let blackhole_addr = blackhole.name().unwrap();
+
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).unwrap();
+    server.listen(8).unwrap();
+    let server_addr = server.name().unwrap();
+
+    //Blackholed candidate delays connection only by attempt delay.
+    let connector = HappyEyeballs::new().attempt_delay(time::Duration::from_millis(50))
+                                        .timeout(time::Duration::from_secs(5));
+    let now = time::Instant::now();
+    let client = connector.connect(&[blackhole_addr, server_addr]).unwrap();
+    let elapsed = now.elapsed();
+    assert!(elapsed >= time::Duration::from_millis(50), "Second attempt started too early: {:?}", elapsed);
+    assert!(elapsed < time::Duration::from_secs(2), "Connection took too long: {:?}", elapsed);
+    let (_peer, client_addr) = server.accept().unwrap();
+    assert_eq!(client.name().unwrap(), client_addr);
+    //Socket is handed over in blocking mode.
+    client.set_read_timeout(Some(time::Duration::from_millis(10))).unwrap();
+    let mut buf = [0u8; 1];
+    let error = client.recv(&mut buf, MsgFlags::empty()).unwrap_err();
+    assert_eq!(error.class(), ErrorClass::WouldBlock);
+
+    //Refused candidate lets the next one start right away.
+    let refused = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    refused.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).unwrap();
+    let refused_addr = refused.name().unwrap();
+    drop(refused);
+
+    let connector = HappyEyeballs::new().attempt_delay(time::Duration::from_secs(10));
+    let now = time::Instant::now();
+    connector.connect(&[refused_addr, server_addr]).unwrap();
+    assert!(now.elapsed() < time::Duration::from_secs(2));
+    server.accept().unwrap();
+
+    let error = connector.connect(&[refused_addr]).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::ConnectionRefused);
+    assert_eq!(error.addr(), Some(refused_addr));
+
+    //Only blackholed candidate is abandoned on timeout.
+    let connector = HappyEyeballs::new().timeout(time::Duration::from_millis(200));
+    let now = time::Instant::now();
+    let error = connector.connect(&[blackhole_addr]).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+    assert!(now.elapsed() >= time::Duration::from_millis(200));
+
+    let error = connector.connect(&[]).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+
+    connector.connect_host(&format!("localhost:{}", server_addr.port())).unwrap();
+    server.accept().unwrap();
+}
@@ -0,0 +1,214 @@
+#![cfg(all(unix, feature = "tokio"))]
+extern crate lazy_socket;
+extern crate tokio;
+
+use std::io;
+use std::net;
+use std::time;
+use std::task;
+use std::thread;
+use std::future::{self, Future};
+use std::str::FromStr;
+
+use lazy_socket::raw::*;
+use lazy_socket::tokio::AsyncSocket;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread().enable_io().build().unwrap()
+}
+
+fn tcp_listener() -> (Socket, net::SocketAddr) {
+    let socket = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(socket.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(socket.listen(1).is_ok());
+    let addr = socket.name().unwrap();
+
+    (socket, addr)
+}
+
+#[test]
+fn tokio_accept_recv() {
+    let data = [1, 2, 3, 4];
+    let rt = runtime();
+    let _guard = rt.enter();
+
+    let (server, server_addr) = tcp_listener();
+    let server = AsyncSocket::new(server).unwrap();
+
+    let th = thread::spawn(move || {
+        let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+        assert!(client.connect(&server_addr).is_ok());
+        assert_eq!(client.send(&data, MsgFlags::empty()).unwrap(), data.len());
+        client.name().unwrap()
+    });
+
+    let (result_socket, result_addr) = rt.block_on(server.accept()).unwrap();
+    assert!(!result_socket.get_ref().get_inheritable().unwrap());
+
+    let mut buf = [0; 10];
+    let result_len = rt.block_on(result_socket.recv(&mut buf)).unwrap();
+    assert_eq!(result_len, data.len());
+    assert_eq!(&buf[..result_len], data);
+
+    assert_eq!(th.join().unwrap(), result_addr);
+}
+
+#[test]
+fn tokio_connect_send() {
+    let data = [1, 2, 3, 4];
+    let rt = runtime();
+    let _guard = rt.enter();
+
+    let (server, server_addr) = tcp_listener();
+
+    let th = thread::spawn(move || {
+        let (socket, _) = server.accept().unwrap();
+        let mut buf = [0; 10];
+        let result_len = socket.recv(&mut buf, MsgFlags::empty()).unwrap();
+        assert_eq!(&buf[..result_len], data);
+    });
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    let client = AsyncSocket::new(client).unwrap();
+    assert!(rt.block_on(client.connect(&server_addr)).is_ok());
+    assert_eq!(rt.block_on(client.send(&data)).unwrap(), data.len());
+
+    assert!(th.join().is_ok());
+}
+
+#[test]
+fn tokio_connect_refused() {
+    let rt = runtime();
+    let _guard = rt.enter();
+
+    //Reserve port and make sure nobody listens on it.
+    let addr = {
+        let (server, _) = tcp_listener();
+        server.name().unwrap()
+    };
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    let client = AsyncSocket::new(client).unwrap();
+    let result = rt.block_on(client.connect(&addr));
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::ConnectionRefused);
+}
+
+#[test]
+fn tokio_connect_in_progress() {
+    let rt = runtime();
+    let _guard = rt.enter();
+
+    //Listener that never accepts drops new connection requests once its backlog is full.
+    let server = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(server.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(server.listen(0).is_ok());
+    let server_addr = server.name().unwrap();
+
+    let mut fillers = Vec::new();
+    let blackholed = (0..16).any(|_| {
+        let filler = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+        let result = filler.connect_timeout(&server_addr, time::Duration::from_millis(200));
+        fillers.push(filler);
+        result.is_err()
+    });
+    assert!(blackholed);
+
+    //Registered before connect, so reactor records readiness of unconnected socket.
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    let client = AsyncSocket::new(client).unwrap();
+    let mut yielded = false;
+    rt.block_on(future::poll_fn(|cx| {
+        if yielded {
+            task::Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            task::Poll::Pending
+        }
+    }));
+
+    let mut connect = Box::pin(client.connect(&server_addr));
+    let mut polls = 0;
+    rt.block_on(future::poll_fn(|cx| {
+        let result = connect.as_mut().poll(cx);
+        assert!(result.is_pending(), "Connect completed while SYN is dropped: {:?}", result);
+
+        //Yield to reactor, so that it delivers pending readiness.
+        polls += 1;
+        if polls < 5 {
+            cx.waker().wake_by_ref();
+            return task::Poll::Pending;
+        }
+
+        task::Poll::Ready(())
+    }));
+}
+
+#[test]
+fn tokio_udp() {
+    let data = [1, 2, 3, 4];
+    let rt = runtime();
+    let _guard = rt.enter();
+    let any_addr = net::SocketAddr::from_str("127.0.0.1:0").unwrap();
+
+    let server = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+    assert!(server.bind(&any_addr).is_ok());
+    let server_addr = server.name().unwrap();
+    let server = AsyncSocket::new(server).unwrap();
+
+    let client = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+    assert!(client.bind(&any_addr).is_ok());
+    let client_addr = client.name().unwrap();
+    let client = AsyncSocket::new(client).unwrap();
+
+    assert_eq!(rt.block_on(client.send_to(&data, &server_addr)).unwrap(), data.len());
+    assert_eq!(rt.block_on(client.send_to(&data, &server_addr)).unwrap(), data.len());
+
+    let mut buf = [0; 10];
+    let (result_len, result_addr) = rt.block_on(server.recv_from(&mut buf)).unwrap();
+    assert_eq!(result_len, data.len());
+    assert_eq!(result_addr, client_addr);
+    assert_eq!(&buf[..result_len], data);
+
+    let mut head = [0; 1];
+    let mut tail = [0; 10];
+    let mut bufs = [io::IoSliceMut::new(&mut head), io::IoSliceMut::new(&mut tail)];
+    let result = rt.block_on(server.recv_msg(&mut bufs, &mut [], MsgFlags::empty())).unwrap();
+    assert_eq!(result.len, data.len());
+    assert_eq!(result.addr, Some(client_addr));
+    assert_eq!(head[0], data[0]);
+    assert_eq!(&tail[..result.len - 1], &data[1..]);
+}
+
+#[test]
+fn tokio_async_read_write() {
+    let data = [1, 2, 3, 4];
+    let rt = runtime();
+    let _guard = rt.enter();
+
+    let (server, server_addr) = tcp_listener();
+
+    let th = thread::spawn(move || {
+        let (socket, _) = server.accept().unwrap();
+        let mut buf = [0; 4];
+        let mut received = 0;
+        while received < buf.len() {
+            received += socket.recv(&mut buf[received..], MsgFlags::empty()).unwrap();
+        }
+        assert_eq!(socket.send(&buf, MsgFlags::empty()).unwrap(), buf.len());
+    });
+
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    let mut client = AsyncSocket::new(client).unwrap();
+    assert!(rt.block_on(client.connect(&server_addr)).is_ok());
+
+    assert!(rt.block_on(client.write_all(&data)).is_ok());
+    let mut buf = [0; 4];
+    assert!(rt.block_on(client.read_exact(&mut buf)).is_ok());
+    assert_eq!(buf, data);
+
+    assert!(rt.block_on(client.shutdown()).is_ok());
+    assert!(th.join().is_ok());
+}
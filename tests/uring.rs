@@ -0,0 +1,218 @@
+#![cfg(all(target_os = "linux", feature = "io-uring"))]
+extern crate lazy_socket;
+extern crate libc;
+
+use std::net;
+use std::thread;
+use std::time;
+use std::str::FromStr;
+
+use lazy_socket::raw::*;
+use lazy_socket::uring::{Ring, Token, Completion};
+
+fn tcp_listener() -> (Socket, net::SocketAddr) {
+    let socket = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+    assert!(socket.bind(&net::SocketAddr::from_str("127.0.0.1:0").unwrap()).is_ok());
+    assert!(socket.listen(2).is_ok());
+    let addr = socket.name().unwrap();
+
+    (socket, addr)
+}
+
+fn wait_for(ring: &mut Ring, pending: &mut Vec<Completion>, token: Token) -> Completion {
+    loop {
+        if let Some(idx) = pending.iter().position(|completion| completion.token() == token) {
+            return pending.remove(idx);
+        }
+
+        assert!(ring.submit_and_wait(1).is_ok());
+        pending.extend(ring.completions());
+    }
+}
+
+#[test]
+fn uring_tcp() {
+    let data = vec![1, 2, 3, 4];
+    let mut ring = Ring::new(8).unwrap();
+    let mut pending = Vec::new();
+    let (server, server_addr) = tcp_listener();
+    let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+
+    let accept = ring.accept(&server, NON_INHERITABLE).unwrap();
+    let connect = ring.connect(&client, &server_addr).unwrap();
+    assert_eq!(ring.in_flight(), 2);
+
+    match wait_for(&mut ring, &mut pending, connect) {
+        Completion::Connect { result, .. } => assert!(result.is_ok()),
+        _ => panic!("Unexpected completion")
+    }
+
+    let accepted = match wait_for(&mut ring, &mut pending, accept) {
+        Completion::Accept { result, more, .. } => {
+            assert!(!more);
+            let (socket, addr) = result.unwrap();
+            assert_eq!(addr, Some(client.name().unwrap()));
+            assert!(!socket.get_inheritable().unwrap());
+            socket
+        },
+        _ => panic!("Unexpected completion")
+    };
+
+    let send = ring.send(&client, data.clone(), MsgFlags::empty()).unwrap();
+    let recv = ring.recv(&accepted, vec![0; 10], MsgFlags::empty()).unwrap();
+
+    match wait_for(&mut ring, &mut pending, send) {
+        Completion::Send { result, buf, .. } => {
+            assert_eq!(result.unwrap(), data.len());
+            assert_eq!(buf, data);
+        },
+        _ => panic!("Unexpected completion")
+    }
+
+    match wait_for(&mut ring, &mut pending, recv) {
+        Completion::Recv { result, buf, .. } => {
+            let len = result.unwrap();
+            assert_eq!(&buf[..len], &data[..]);
+        },
+        _ => panic!("Unexpected completion")
+    }
+
+    assert_eq!(ring.in_flight(), 0);
+}
+
+#[test]
+fn uring_multishot() {
+    let group = 1;
+    let mut ring = Ring::new(8).unwrap();
+    let mut pending = Vec::new();
+    let (server, server_addr) = tcp_listener();
+
+    assert!(ring.register_buf_ring(group, 4, 16).is_ok());
+    assert!(ring.register_buf_ring(group, 4, 16).is_err());
+
+    let accept = ring.accept_multi(&server, AcceptFlags::empty()).unwrap();
+
+    let clients: Vec<Socket> = (0..2).map(|_| {
+        let client = Socket::new(Domain::IPv4, SockType::STREAM, Protocol::TCP).unwrap();
+        assert!(client.connect(&server_addr).is_ok());
+        client
+    }).collect();
+
+    let mut accepted = Vec::new();
+    for _ in 0..clients.len() {
+        match wait_for(&mut ring, &mut pending, accept) {
+            Completion::Accept { result, more, .. } => {
+                assert!(more);
+                let (socket, addr) = result.unwrap();
+                assert!(addr.is_none());
+                accepted.push(socket);
+            },
+            _ => panic!("Unexpected completion")
+        }
+    }
+    assert_eq!(ring.in_flight(), 1);
+
+    let recv = ring.recv_multi(&accepted[0], group, MsgFlags::empty()).unwrap();
+    let mut kept = None;
+    for chunk in [b"hello", b"world"].iter() {
+        assert_eq!(clients[0].send(&chunk[..], MsgFlags::empty()).unwrap(), chunk.len());
+
+        match wait_for(&mut ring, &mut pending, recv) {
+            Completion::RecvProvided { result, more, .. } => {
+                assert!(more);
+                let buf = result.unwrap().unwrap();
+                assert_eq!(buf.group(), group);
+                assert_eq!(ring.provided(&buf).unwrap(), &chunk[..]);
+                match kept {
+                    None => kept = Some(buf),
+                    Some(_) => ring.recycle(buf)
+                }
+            },
+            _ => panic!("Unexpected completion")
+        }
+
+        thread::sleep(time::Duration::from_millis(10));
+    }
+
+    //Peer closure ends multishot receive.
+    drop(clients);
+    match wait_for(&mut ring, &mut pending, recv) {
+        Completion::RecvProvided { result, more, .. } => {
+            assert!(!more);
+            assert!(result.unwrap().is_none());
+        },
+        _ => panic!("Unexpected completion")
+    }
+
+    assert!(ring.cancel(accept).is_ok());
+    match wait_for(&mut ring, &mut pending, accept) {
+        Completion::Accept { result, more, .. } => {
+            assert!(!more);
+            assert_eq!(result.err().unwrap().raw_os_error(), Some(libc::ECANCELED));
+        },
+        _ => panic!("Unexpected completion")
+    }
+
+    assert_eq!(ring.in_flight(), 0);
+    assert!(ring.unregister_buf_ring(group).is_ok());
+    //Content of buffer is gone along with its ring.
+    let kept = kept.unwrap();
+    assert!(ring.provided(&kept).is_none());
+
+    //Buffer doesn't belong to ring registered under the same group later.
+    assert!(ring.register_buf_ring(group, 1, 2).is_ok());
+    assert!(ring.provided(&kept).is_none());
+    ring.recycle(kept);
+
+    assert!(ring.register_buf_ring(group + 1, 4, 0).is_err());
+}
+
+#[test]
+fn uring_udp_msg() {
+    let data = vec![1, 2, 3, 4];
+    let any_addr = net::SocketAddr::from_str("127.0.0.1:0").unwrap();
+    let mut ring = Ring::new(8).unwrap();
+    let mut pending = Vec::new();
+
+    let server = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+    assert!(server.bind(&any_addr).is_ok());
+    let server_addr = server.name().unwrap();
+
+    let client = Socket::new(Domain::IPv4, SockType::DATAGRAM, Protocol::UDP).unwrap();
+    assert!(client.bind(&any_addr).is_ok());
+    let client_addr = client.name().unwrap();
+
+    let recv = ring.recv_msg(&server, vec![0; 2], Vec::new(), MsgFlags::empty()).unwrap();
+    let send = ring.send_msg(&client, data.clone(), Vec::new(), Some(&server_addr), MsgFlags::empty()).unwrap();
+
+    match wait_for(&mut ring, &mut pending, send) {
+        Completion::SendMsg { result, buf, .. } => {
+            assert_eq!(result.unwrap(), data.len());
+            assert_eq!(buf, data);
+        },
+        _ => panic!("Unexpected completion")
+    }
+
+    match wait_for(&mut ring, &mut pending, recv) {
+        Completion::RecvMsg { result, buf, addr, flags, .. } => {
+            assert_eq!(result.unwrap(), 2);
+            assert_eq!(&buf[..], &data[..2]);
+            assert_eq!(addr, Some(client_addr));
+            assert!(flags.contains(TRUNC));
+        },
+        _ => panic!("Unexpected completion")
+    }
+}
+
+#[test]
+fn uring_drop_in_flight() {
+    let (server, _) = tcp_listener();
+    let mut ring = Ring::new(8).unwrap();
+
+    assert!(ring.accept(&server, AcceptFlags::empty()).is_ok());
+    assert!(ring.submit().is_ok());
+    assert_eq!(ring.in_flight(), 1);
+
+    //Pending accept is cancelled and reaped.
+    drop(ring);
+}